@@ -12,6 +12,13 @@ extern crate error_chain;
 
 mod master_smasher;
 
+use master_smasher::{Generator, HeadlessLoader, LevelAssets, LevelData, Replay, Severity,
+                     Simulation, Solver, load_valid, validate};
+
+use std::env;
+use std::path::Path;
+use std::process;
+
 pub mod errors {
     error_chain!{
         links {
@@ -20,16 +27,189 @@ pub mod errors {
         foreign_links {
             Io(::std::io::Error);
             Yaml(::serde_yaml::Error);
+            ParseInt(::std::num::ParseIntError);
+        }
+        errors {
+            Unsupported(feature: String) {
+                description("level feature the solver does not support")
+                display("cannot solve levels with {}", feature)
+            }
         }
     }
 }
 
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 720;
+
 fn main() {
-    const WINDOW_WIDTH: u32 = 1280;
-    const WINDOW_HEIGHT: u32 = 720;
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("simulate") => simulate(&args[1..]).unwrap(),
+        Some("replay") => replay(&args[1..]).unwrap(),
+        Some("solve") => solve(&args[1..]).unwrap(),
+        Some("generate") => generate(&args[1..]).unwrap(),
+        Some("lint") => {
+            if !lint(&args[1..]).unwrap() {
+                process::exit(1);
+            }
+        }
+        _ => play(&args).unwrap(),
+    }
+}
+
+fn play(args: &[String]) -> errors::Result<()> {
     let (renderer, input_manager) = moho::init("Master Smasher", WINDOW_WIDTH, WINDOW_HEIGHT)
         .unwrap();
-    let mut game =
-        master_smasher::MasterSmasher::<moho::SdlMohoEngine>::new(renderer, input_manager).unwrap();
-    game.run().unwrap();
+    let mut game = master_smasher::MasterSmasher::<moho::SdlMohoEngine>::new(renderer,
+                                                                            input_manager)?;
+    match args.first().map(String::as_str) {
+        Some("watch") => {
+            let path = args.get(1).ok_or("usage: master_smasher watch <replay>")?;
+            game.watch(&Replay::load(path)?)?;
+        }
+        Some("edit") => {
+            let path = args.get(1).ok_or("usage: master_smasher edit <level>")?;
+            game.edit(path)?;
+        }
+        _ => {}
+    }
+    let result = game.run();
+    for warning in game.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+    result
+}
+
+/// Runs a recorded replay headless and reports how it ended.
+fn replay(args: &[String]) -> errors::Result<()> {
+    let path = args.first().ok_or("usage: master_smasher replay <replay>")?;
+    let replay = Replay::load(path)?;
+    let window_size = glm::uvec2(WINDOW_WIDTH, WINDOW_HEIGHT);
+    let assets = LevelAssets::new(&HeadlessLoader::default())?;
+    let data = load_level(replay.level(), window_size, &assets)?;
+    let mut simulation = Simulation::new(data, window_size, assets);
+    for input in replay.inputs() {
+        simulation.step(&input);
+    }
+    report(&simulation);
+    Ok(())
+}
+
+fn simulate(args: &[String]) -> errors::Result<()> {
+    const MAX_FLIGHT_TICKS: u32 = 60 * 30;
+
+    let path = args.first().ok_or("usage: master_smasher simulate <level> [<x> <y>]...")?;
+    let window_size = glm::uvec2(WINDOW_WIDTH, WINDOW_HEIGHT);
+    let assets = LevelAssets::new(&HeadlessLoader::default())?;
+    let data = load_level(path, window_size, &assets)?;
+    let mut simulation = Simulation::new(data, window_size, assets);
+
+    for (i, coords) in args[1..].chunks(2).enumerate() {
+        if coords.len() != 2 {
+            bail!("target {} is missing its y coordinate", i + 1);
+        }
+        let target = glm::ivec2(coords[0].parse()?, coords[1].parse()?);
+        simulation.shoot(target, MAX_FLIGHT_TICKS);
+        println!("shot {} at ({}, {}): {} enemies left, {} stars left",
+                 i + 1,
+                 target.x,
+                 target.y,
+                 simulation.enemies_left(),
+                 simulation.stars_left());
+    }
+
+    report(&simulation);
+    Ok(())
+}
+
+fn solve(args: &[String]) -> errors::Result<()> {
+    let path = args.first().ok_or("usage: master_smasher solve <level>")?;
+    let window_size = glm::uvec2(WINDOW_WIDTH, WINDOW_HEIGHT);
+    let assets = LevelAssets::new(&HeadlessLoader::default())?;
+    let data = load_level(path, window_size, &assets)?;
+    let limit = data.shots;
+    let solution = match Solver::new(data, window_size, assets).solve()? {
+        Some(s) => s,
+        None => bail!("no solution found for {}", path),
+    };
+
+    let shots = solution.targets.len() as u32;
+    match limit {
+        Some(l) if shots > l => println!("needs {} shots but only {} are allowed", shots, l),
+        _ => println!("solvable in {} shots", shots),
+    }
+    for target in &solution.targets {
+        println!("  aim at ({}, {})", target.x, target.y);
+    }
+    Ok(())
+}
+
+/// Prints every problem with each level, returning whether none of them had errors.
+fn lint(paths: &[String]) -> errors::Result<bool> {
+    if paths.is_empty() {
+        bail!("usage: master_smasher lint <level>...");
+    }
+
+    let window_size = glm::uvec2(WINDOW_WIDTH, WINDOW_HEIGHT);
+    let assets = LevelAssets::new(&HeadlessLoader::default())?;
+    let mut clean = true;
+    for path in paths {
+        let data = match LevelData::load(path) {
+            Ok(d) => d,
+            Err(e) => {
+                let mut causes: Vec<String> = e.iter().map(|c| c.to_string()).collect();
+                causes.dedup();
+                println!("{}: error: {}", path, causes.join(": "));
+                clean = false;
+                continue;
+            }
+        };
+        for d in validate(&data, window_size, &assets) {
+            clean &= d.severity != Severity::ERROR;
+            println!("{}: {}", path, d);
+        }
+    }
+    Ok(clean)
+}
+
+fn generate(args: &[String]) -> errors::Result<()> {
+    const ATTEMPTS: u32 = 50;
+
+    if args.len() != 3 {
+        bail!("usage: master_smasher generate <seed> <shots> <output.lvl>");
+    }
+    let seed = args[0].parse()?;
+    let shots = args[1].parse()?;
+    let window_size = glm::uvec2(WINDOW_WIDTH, WINDOW_HEIGHT);
+    let assets = LevelAssets::new(&HeadlessLoader::default())?;
+    let data = Generator::new(seed, window_size, assets).generate(shots, ATTEMPTS)?;
+    data.save(&args[2])?;
+    println!("wrote {}", args[2]);
+    Ok(())
+}
+
+/// Loads a level to run headless, printing its warnings.
+fn load_level<P: AsRef<Path>>(path: P,
+                              window_size: glm::UVec2,
+                              assets: &LevelAssets)
+                              -> errors::Result<LevelData> {
+    let (data, warnings) = load_valid(path, window_size, assets)?;
+    for warning in warnings {
+        eprintln!("{}", warning);
+    }
+    Ok(data)
+}
+
+fn report(simulation: &Simulation) {
+    let score = simulation.score();
+    match simulation.outcome() {
+        Some(outcome) => {
+            println!("{:?} after {} shots with {}/{} stars",
+                     outcome,
+                     score.shots,
+                     score.stars,
+                     score.total_stars)
+        }
+        None => println!("level not over after {} shots", score.shots),
+    }
 }
@@ -0,0 +1,64 @@
+use errors::*;
+
+use serde_yaml;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug,Deserialize)]
+struct Manifest {
+    levels: Vec<String>,
+}
+
+/// Ordered list of level files; paths in the manifest are relative to the manifest itself.
+pub struct Campaign {
+    levels: Vec<PathBuf>,
+}
+
+impl Campaign {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Campaign> {
+        let path = path.as_ref();
+        let f = File::open(path)?;
+        let manifest: Manifest = serde_yaml::from_reader(&f)?;
+        if manifest.levels.is_empty() {
+            bail!("campaign {} has no levels", path.display());
+        }
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let levels = manifest.levels.iter().map(|l| dir.join(l)).collect();
+        Ok(Campaign { levels: levels })
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn level(&self, index: usize) -> Option<&Path> {
+        self.levels.get(index).map(PathBuf::as_path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::{HeadlessLoader, LevelAssets, load_valid};
+    use glm;
+
+    #[test]
+    fn resolves_levels_next_to_manifest() {
+        let campaign = Campaign::load("levels/campaign.yaml").unwrap();
+        assert_eq!(campaign.level(0), Some(Path::new("levels/level_1.lvl")));
+        assert_eq!(campaign.level(campaign.len()), None);
+    }
+
+    #[test]
+    fn every_level_is_valid() {
+        let campaign = Campaign::load("levels/campaign.yaml").unwrap();
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        for i in 0..campaign.len() {
+            let level = campaign.level(i).unwrap();
+            let (_, warnings) = load_valid(level, glm::uvec2(1280, 720), &assets).unwrap();
+            assert!(warnings.is_empty());
+        }
+    }
+}
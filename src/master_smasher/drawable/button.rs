@@ -0,0 +1,30 @@
+use super::layout;
+
+use glm;
+use moho::resource_manager::{Renderer, Scene, Texture, TextureId};
+use moho::errors as moho_errors;
+
+#[derive(Clone, Copy)]
+pub struct Button {
+    pub texture: TextureId,
+    pub dst_rect: glm::IVec4,
+}
+
+impl Button {
+    pub fn new(texture: Texture, center: glm::IVec2) -> Self {
+        Button {
+            texture: texture.id,
+            dst_rect: layout::centered(glm::to_ivec2(texture.dims), center),
+        }
+    }
+
+    pub fn contains(&self, point: glm::IVec2) -> bool {
+        layout::contains(self.dst_rect, point)
+    }
+}
+
+impl Scene for Button {
+    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
+        renderer.render(&self.texture, self.dst_rect)
+    }
+}
@@ -0,0 +1,60 @@
+use glm;
+use sdl2::rect::Rect;
+
+use std::cmp;
+
+// segments lit for each digit: top, top right, bottom right, bottom, bottom left, top left,
+// middle
+const SEGMENTS: [[bool; 7]; 10] = [[true, true, true, true, true, true, false],
+                                   [false, true, true, false, false, false, false],
+                                   [true, true, false, true, true, false, true],
+                                   [true, true, true, true, false, false, true],
+                                   [false, true, true, false, false, true, true],
+                                   [true, false, true, true, false, true, true],
+                                   [true, false, true, true, true, true, true],
+                                   [true, true, true, false, false, false, false],
+                                   [true, true, true, true, true, true, true],
+                                   [true, true, true, true, false, true, true]];
+
+/// Seven-segment rendering of `value` whose top left corner is at `position`; each digit is
+/// `width` wide and twice as tall.
+pub fn number(value: u32, position: glm::IVec2, width: u32, rects: &mut Vec<Rect>) {
+    let text = value.to_string();
+    let advance = width as i32 * 3 / 2;
+    for (i, c) in text.chars().enumerate() {
+        let digit = c.to_digit(10).unwrap_or(0) as usize;
+        let origin = position + glm::ivec2(i as i32 * advance, 0);
+        digit_rects(digit, origin, width, rects);
+    }
+}
+
+fn digit_rects(digit: usize, origin: glm::IVec2, width: u32, rects: &mut Vec<Rect>) {
+    let thick = cmp::max(width / 5, 1);
+    let w = width as i32;
+    let t = thick as i32;
+    let segments = [(0, 0, width, thick),
+                    (w - t, 0, thick, width),
+                    (w - t, w, thick, width),
+                    (0, 2 * w - t, width, thick),
+                    (0, w, thick, width),
+                    (0, 0, thick, width),
+                    (0, w - t / 2, width, thick)];
+    for (lit, &(x, y, sw, sh)) in SEGMENTS[digit].iter().zip(&segments) {
+        if *lit {
+            rects.push(Rect::new(origin.x + x, origin.y + y, sw, sh));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lights_segments_per_digit() {
+        let mut rects = vec![];
+        number(18, glm::ivec2(0, 0), 10, &mut rects);
+        assert_eq!(rects.len(), 2 + 7);
+        assert!(rects[2..].iter().all(|r| r.x() >= 15));
+    }
+}
@@ -0,0 +1,48 @@
+use glm;
+
+pub fn centered(dims: glm::IVec2, center: glm::IVec2) -> glm::IVec4 {
+    glm::ivec4(center.x - dims.x / 2, center.y - dims.y / 2, dims.x, dims.y)
+}
+
+pub fn contains(rect: glm::IVec4, point: glm::IVec2) -> bool {
+    point.x >= rect.x && point.x < rect.x + rect.z && point.y >= rect.y && point.y < rect.y + rect.w
+}
+
+/// Rectangles of `dims` laid out in a row centered on `center`, a third of a width apart.
+pub fn row(count: u32, dims: glm::IVec2, center: glm::IVec2) -> Vec<glm::IVec4> {
+    let gap = dims.x / 3;
+    let spacing = dims.x + gap;
+    let start = center.x - (spacing * count as i32 - gap) / 2;
+    (0..count as i32)
+        .map(|i| glm::ivec4(start + spacing * i, center.y - dims.y / 2, dims.x, dims.y))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn centered_rect() {
+        let rect = centered(glm::ivec2(10, 4), glm::ivec2(20, 20));
+        assert_eq!(rect, glm::ivec4(15, 18, 10, 4));
+    }
+
+    #[test]
+    fn contains_point() {
+        let rect = glm::ivec4(15, 18, 10, 4);
+        assert!(contains(rect, glm::ivec2(15, 18)));
+        assert!(contains(rect, glm::ivec2(24, 21)));
+        assert!(!contains(rect, glm::ivec2(25, 21)));
+        assert!(!contains(rect, glm::ivec2(20, 17)));
+    }
+
+    #[test]
+    fn centered_row() {
+        let rects = row(3, glm::ivec2(30, 10), glm::ivec2(100, 50));
+        assert_eq!(rects,
+                   vec![glm::ivec4(45, 45, 30, 10),
+                        glm::ivec4(85, 45, 30, 10),
+                        glm::ivec4(125, 45, 30, 10)]);
+    }
+}
@@ -1,8 +1,15 @@
 pub mod animation;
 pub mod animation_data;
+pub mod button;
+pub mod digits;
+pub mod layout;
+pub mod outline;
+pub mod star_rating;
 
 pub use self::animation_data::AnimationData;
 pub use self::animation::Animation;
+pub use self::button::Button;
+pub use self::star_rating::{StarRating, StarRatingAssets};
 
 use master_smasher::shape::Circle;
 
@@ -0,0 +1,77 @@
+//! Dotted outlines built out of filled rects, the only primitive the renderer has besides
+//! textures.
+
+use master_smasher::shape::{Circle, Rectangle};
+
+use glm;
+use sdl2::rect::Rect;
+
+use std::f64::consts::PI;
+
+const DOT: u32 = 2;
+const SPACING: f64 = 4.;
+
+fn dot(point: glm::DVec2, rects: &mut Vec<Rect>) {
+    rects.push(Rect::from_center((point.x as i32, point.y as i32), DOT, DOT));
+}
+
+pub fn segment(from: glm::DVec2, to: glm::DVec2, rects: &mut Vec<Rect>) {
+    let length = glm::distance(from, to);
+    let dots = (length / SPACING).ceil().max(1.) as u32;
+    for i in 0..dots + 1 {
+        dot(from + (to - from) * (i as f64 / dots as f64), rects);
+    }
+}
+
+/// A segment with a solid head at `to`.
+pub fn arrow(from: glm::DVec2, to: glm::DVec2, rects: &mut Vec<Rect>) {
+    segment(from, to, rects);
+    rects.push(Rect::from_center((to.x as i32, to.y as i32), DOT * 2, DOT * 2));
+}
+
+pub fn circle(circle: &Circle, rects: &mut Vec<Rect>) {
+    let dots = (2. * PI * circle.radius / SPACING).ceil().max(8.) as u32;
+    for i in 0..dots {
+        let angle = 2. * PI * i as f64 / dots as f64;
+        dot(circle.center + glm::dvec2(angle.cos(), angle.sin()) * circle.radius,
+            rects);
+    }
+}
+
+pub fn rectangle(rectangle: &Rectangle, rects: &mut Vec<Rect>) {
+    let half = rectangle.dims / 2.;
+    let corners = [rectangle.center + glm::dvec2(-half.x, -half.y),
+                   rectangle.center + glm::dvec2(half.x, -half.y),
+                   rectangle.center + half,
+                   rectangle.center + glm::dvec2(-half.x, half.y)];
+    for i in 0..corners.len() {
+        segment(corners[i], corners[(i + 1) % corners.len()], rects);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn segment_covers_both_ends() {
+        let mut rects = vec![];
+        segment(glm::dvec2(0., 0.), glm::dvec2(10., 0.), &mut rects);
+        assert_eq!(rects.first().unwrap().center().x(), 0);
+        assert_eq!(rects.last().unwrap().center().x(), 10);
+    }
+
+    #[test]
+    fn circle_dots_lie_on_circle() {
+        let circle_shape = Circle {
+            center: glm::dvec2(50., 50.),
+            radius: 20.,
+        };
+        let mut rects = vec![];
+        circle(&circle_shape, &mut rects);
+        for r in &rects {
+            let center = glm::dvec2(r.center().x() as f64, r.center().y() as f64);
+            assert!((glm::distance(center, circle_shape.center) - 20.).abs() < 2.);
+        }
+    }
+}
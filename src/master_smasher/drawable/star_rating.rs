@@ -0,0 +1,65 @@
+use super::layout;
+
+use glm;
+use moho::resource_manager::{Renderer, ResourceLoader, Scene, Texture, TileSheet};
+use moho::errors as moho_errors;
+
+#[derive(Clone)]
+pub struct StarRatingAssets {
+    star: TileSheet,
+    missed: Texture,
+}
+
+impl StarRatingAssets {
+    pub fn new<L: ResourceLoader>(resource_loader: &L) -> moho_errors::Result<Self> {
+        let star = resource_loader.load_texture("resources/star.png")?;
+        let missed = resource_loader.load_texture("resources/dot.png")?;
+        Ok(StarRatingAssets {
+               star: TileSheet::new(glm::uvec2(2, 1), star),
+               missed: missed,
+           })
+    }
+}
+
+/// A row with a star per star in a level, collected ones drawn full and missed ones as dots.
+pub struct StarRating {
+    stars: Vec<(glm::IVec4, bool)>,
+    assets: StarRatingAssets,
+}
+
+impl StarRating {
+    pub fn new(earned: u32,
+               total: u32,
+               scale: f64,
+               center: glm::IVec2,
+               assets: StarRatingAssets)
+               -> Self {
+        let dims = glm::to_ivec2(glm::to_dvec2(assets.star.dimensions) * scale);
+        let stars = layout::row(total, dims, center)
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| (r, (i as u32) < earned))
+            .collect();
+
+        StarRating {
+            stars: stars,
+            assets: assets,
+        }
+    }
+}
+
+impl Scene for StarRating {
+    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
+        let star = self.assets.star.tile(0);
+        for &(rect, earned) in &self.stars {
+            if earned {
+                renderer.render(&star, rect)?;
+            } else {
+                let center = glm::ivec2(rect.x + rect.z / 2, rect.y + rect.w / 2);
+                let dot = layout::centered(glm::ivec2(rect.z, rect.w) / 3, center);
+                renderer.render(&self.assets.missed.id, dot)?;
+            }
+        }
+        Ok(())
+    }
+}
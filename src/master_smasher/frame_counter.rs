@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+/// Counts frames drawn and updates run, reporting both once a second.
+#[derive(Debug, Default)]
+pub struct FrameCounter {
+    frames: u32,
+    updates: u32,
+    elapsed: Duration,
+    /// Frames drawn over the last full second.
+    pub fps: u32,
+    /// Updates run over the last full second.
+    pub ups: u32,
+}
+
+impl FrameCounter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Counts a drawn frame that came `delta` after the previous one.
+    pub fn frame(&mut self, delta: Duration) {
+        self.frames += 1;
+        self.elapsed += delta;
+        let second = Duration::from_secs(1);
+        if self.elapsed >= second {
+            self.fps = self.frames;
+            self.ups = self.updates;
+            self.frames = 0;
+            self.updates = 0;
+            self.elapsed -= second;
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.updates += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reports_once_a_second() {
+        let mut counter = FrameCounter::new();
+        let frame = Duration::from_millis(250);
+        for _ in 0..3 {
+            counter.update();
+            counter.update();
+            counter.frame(frame);
+        }
+        assert_eq!((counter.fps, counter.ups), (0, 0));
+
+        counter.update();
+        counter.frame(frame);
+        assert_eq!((counter.fps, counter.ups), (4, 7));
+
+        counter.frame(frame);
+        assert_eq!((counter.fps, counter.ups), (4, 7));
+    }
+}
@@ -0,0 +1,302 @@
+use master_smasher::{LevelAssets, Solver};
+use master_smasher::level::{Edges, Gravity, LevelData, ObjectData, Physics, PlanetData,
+                            PlanetKind};
+use errors::*;
+
+use glm;
+
+/// Space left between bodies and from the window's edges.
+const PADDING: f64 = 20.;
+const PLACEMENT_ATTEMPTS: u32 = 100;
+
+/// xorshift64*: small, fast and the same on every platform, which is all a seed needs.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // the all-zero state never leaves zero
+        Rng { state: if seed == 0 { 0x9e3779b97f4a7c15 } else { seed } }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    /// Uniform in `[low, high)`.
+    fn range(&mut self, low: u32, high: u32) -> u32 {
+        low + (self.next() % (high - low) as u64) as u32
+    }
+
+    /// Uniform in `[low, high)`.
+    fn range_f64(&mut self, low: f64, high: f64) -> f64 {
+        let unit = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        low + unit * (high - low)
+    }
+}
+
+/// A body already placed. Bodies that both have a reach also keep that far apart: rings don't
+/// overlap each other or the meteor's starting point.
+struct Placed {
+    center: glm::DVec2,
+    radius: f64,
+    reach: Option<f64>,
+}
+
+/// Builds random levels from a seed and keeps only the ones the solver can clear.
+pub struct Generator {
+    rng: Rng,
+    window_size: glm::UVec2,
+    assets: LevelAssets,
+}
+
+impl Generator {
+    pub fn new(seed: u64, window_size: glm::UVec2, assets: LevelAssets) -> Self {
+        Generator {
+            rng: Rng::new(seed),
+            window_size: window_size,
+            assets: assets,
+        }
+    }
+
+    /// Tries up to `attempts` layouts and returns the first one clearable in `shots` shots,
+    /// with that limit written into the level.
+    pub fn generate(&mut self, shots: u32, attempts: u32) -> Result<LevelData> {
+        for _ in 0..attempts {
+            let mut data = match self.layout() {
+                Some(d) => d,
+                None => continue,
+            };
+            let solver = Solver::new(data.clone(), self.window_size, self.assets.clone());
+            if let Some(solution) = solver.solve()? {
+                if solution.targets.len() as u32 <= shots {
+                    data.shots = Some(shots);
+                    return Ok(data);
+                }
+            }
+        }
+        bail!("no clearable level in {} attempts", attempts)
+    }
+
+    /// A random level with no overlapping bodies, or `None` if they didn't fit.
+    fn layout(&mut self) -> Option<LevelData> {
+        let mut bodies = vec![];
+        let radius = self.assets.meteor_radius();
+        let meteor = self.place(radius, Some(radius), &mut bodies)?;
+
+        let mut planets = vec![];
+        for _ in 0..self.rng.range(1, 4) {
+            let mut planet = PlanetData {
+                x: 0,
+                y: 0,
+                kind: self.planet_kind(),
+                gravity: Gravity::default(),
+                bouncy: None,
+                motion: None,
+            };
+            let radius = self.assets.planet_radius(&planet);
+            let center = self.place(radius, ring(&planet.kind), &mut bodies)?;
+            planet.x = center.x;
+            planet.y = center.y;
+            planets.push(planet);
+        }
+
+        let mut stars = vec![];
+        for _ in 0..3 {
+            let radius = self.assets.star_radius();
+            stars.push(self.place(radius, None, &mut bodies)?);
+        }
+        let mut enemies = vec![];
+        for _ in 0..self.rng.range(1, 4) {
+            let radius = self.assets.enemy_radius();
+            enemies.push(self.place(radius, None, &mut bodies)?);
+        }
+
+        Some(LevelData {
+                 meteor: meteor,
+                 stars: stars,
+                 enemies: enemies,
+                 stations: vec![],
+                 meteors: vec![],
+                 planets: planets,
+                 portals: vec![],
+                 edges: Edges::default(),
+                 physics: Physics::default(),
+                 preview: None,
+                 shots: None,
+                 fuel: None,
+             })
+    }
+
+    fn planet_kind(&mut self) -> PlanetKind {
+        let ring = self.rng.range_f64(150., 250.).round();
+        let strength = self.rng.range_f64(300., 800.).round();
+        match self.rng.range(0, 5) {
+            0 => {
+                PlanetKind::RED {
+                    ring: ring,
+                    strength: strength,
+                }
+            }
+            1 => {
+                PlanetKind::BLUE {
+                    ring: ring,
+                    strength: strength,
+                }
+            }
+            2 => {
+                PlanetKind::WHITE {
+                    ring: ring,
+                    strength: strength,
+                }
+            }
+            3 => {
+                PlanetKind::PURPLE {
+                    ring: ring,
+                    strength: strength,
+                }
+            }
+            _ => PlanetKind::DEAD,
+        }
+    }
+
+    /// Picks a spot inside the window clear of every body placed so far.
+    fn place(&mut self,
+             radius: f64,
+             reach: Option<f64>,
+             bodies: &mut Vec<Placed>)
+             -> Option<ObjectData> {
+        let margin = radius + PADDING;
+        let size = glm::to_dvec2(self.window_size);
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let center = glm::round(glm::dvec2(self.rng.range_f64(margin, size.x - margin),
+                                               self.rng.range_f64(margin, size.y - margin)));
+            let placed = Placed {
+                center: center,
+                radius: radius,
+                reach: reach,
+            };
+            if bodies.iter().all(|b| is_clear(b, &placed)) {
+                bodies.push(placed);
+                return Some(ObjectData {
+                                x: center.x as i32,
+                                y: center.y as i32,
+                            });
+            }
+        }
+        None
+    }
+}
+
+fn ring(kind: &PlanetKind) -> Option<f64> {
+    match *kind {
+        PlanetKind::RED { ring, .. } |
+        PlanetKind::BLUE { ring, .. } |
+        PlanetKind::WHITE { ring, .. } |
+        PlanetKind::PURPLE { ring, .. } => Some(ring),
+        PlanetKind::DEAD => None,
+    }
+}
+
+fn is_clear(a: &Placed, b: &Placed) -> bool {
+    let distance = glm::distance(a.center, b.center);
+    let reaches = match (a.reach, b.reach) {
+        (Some(r), Some(s)) => distance >= r + s,
+        _ => true,
+    };
+    reaches && distance >= a.radius + b.radius + PADDING
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::HeadlessLoader;
+
+    fn generator(seed: u64) -> Generator {
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        Generator::new(seed, glm::uvec2(1280, 720), assets)
+    }
+
+    fn bodies(data: &LevelData) -> Vec<glm::IVec2> {
+        let mut bodies: Vec<glm::IVec2> = vec![(&data.meteor).into()];
+        bodies.extend(data.planets.iter().map(|p| glm::ivec2(p.x, p.y)));
+        bodies.extend(data.stars.iter().map(glm::IVec2::from));
+        bodies.extend(data.enemies.iter().map(glm::IVec2::from));
+        bodies
+    }
+
+    fn placed(data: &LevelData, assets: &LevelAssets) -> Vec<Placed> {
+        let at = |x: i32, y: i32| glm::dvec2(x as f64, y as f64);
+        let meteor = assets.meteor_radius();
+        let mut placed = vec![Placed {
+                                  center: at(data.meteor.x, data.meteor.y),
+                                  radius: meteor,
+                                  reach: Some(meteor),
+                              }];
+        placed.extend(data.planets.iter().map(|p| {
+            Placed {
+                center: at(p.x, p.y),
+                radius: assets.planet_radius(p),
+                reach: ring(&p.kind),
+            }
+        }));
+        let targets = data.stars
+            .iter()
+            .map(|s| (s, assets.star_radius()))
+            .chain(data.enemies.iter().map(|e| (e, assets.enemy_radius())));
+        placed.extend(targets.map(|(t, radius)| {
+            Placed {
+                center: at(t.x, t.y),
+                radius: radius,
+                reach: None,
+            }
+        }));
+        placed
+    }
+
+    #[test]
+    fn same_seed_same_layout() {
+        let first = generator(7).layout().unwrap();
+        let second = generator(7).layout().unwrap();
+        assert_eq!(bodies(&first), bodies(&second));
+    }
+
+    #[test]
+    fn bodies_and_rings_do_not_overlap() {
+        let mut generator = generator(42);
+        let mut laid_out = 0;
+        let mut repulsors = false;
+        for _ in 0..50 {
+            if let Some(data) = generator.layout() {
+                laid_out += 1;
+                repulsors |= data.planets.iter().any(|p| match p.kind {
+                                                         PlanetKind::PURPLE { .. } => true,
+                                                         _ => false,
+                                                     });
+                let placed = placed(&data, &generator.assets);
+                for (i, a) in placed.iter().enumerate() {
+                    assert!(a.center.x > 0. && a.center.x < 1280. && a.center.y > 0. &&
+                            a.center.y < 720.);
+                    for b in &placed[i + 1..] {
+                        assert!(is_clear(a, b));
+                    }
+                }
+            }
+        }
+        assert!(laid_out > 0 && repulsors);
+    }
+
+    #[test]
+    fn generated_level_is_clearable() {
+        let data = generator(1).generate(3, 20).unwrap();
+        assert_eq!(data.shots, Some(3));
+
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let solution = Solver::new(data, glm::uvec2(1280, 720), assets).solve().unwrap().unwrap();
+        assert!(solution.targets.len() <= 3);
+    }
+}
@@ -0,0 +1,78 @@
+use glm;
+use moho::errors::*;
+use moho::resource_manager::{ResourceLoader, Texture, TextureId};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+
+/// Hands out textures without a renderer by only reading the image dimensions from the PNG header.
+#[derive(Default)]
+pub struct HeadlessLoader {
+    cache: RefCell<HashMap<&'static str, Texture>>,
+}
+
+impl HeadlessLoader {
+    fn read_dims(path: &str) -> Result<glm::UVec2> {
+        const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+        let mut header = [0; 24];
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+        file.read_exact(&mut header).map_err(|e| e.to_string())?;
+        if header[..8] != SIGNATURE || &header[12..16] != b"IHDR" {
+            bail!("{} is not a PNG image", path);
+        }
+
+        let width = Self::read_u32(&header[16..20]);
+        let height = Self::read_u32(&header[20..24]);
+        Ok(glm::uvec2(width, height))
+    }
+
+    fn read_u32(bytes: &[u8]) -> u32 {
+        bytes.iter().fold(0, |n, &b| n << 8 | b as u32)
+    }
+}
+
+impl ResourceLoader for HeadlessLoader {
+    fn load_texture(&self, path: &'static str) -> Result<Texture> {
+        if let Some(texture) = self.cache.borrow().get(path) {
+            return Ok(*texture);
+        }
+
+        let texture = Texture {
+            id: TextureId(self.cache.borrow().len()),
+            dims: Self::read_dims(path)?,
+        };
+        self.cache.borrow_mut().insert(path, texture);
+        Ok(texture)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_png_dimensions() {
+        let loader = HeadlessLoader::default();
+        let texture = loader.load_texture("resources/meteor.png").unwrap();
+        assert_eq!(texture.dims, glm::uvec2(60, 60));
+    }
+
+    #[test]
+    fn caches_textures() {
+        let loader = HeadlessLoader::default();
+        let meteor = loader.load_texture("resources/meteor.png").unwrap();
+        let star = loader.load_texture("resources/star.png").unwrap();
+        let cached = loader.load_texture("resources/meteor.png").unwrap();
+        assert_eq!(meteor.id, cached.id);
+        assert!(meteor.id != star.id);
+    }
+
+    #[test]
+    fn rejects_non_png() {
+        let loader = HeadlessLoader::default();
+        assert!(loader.load_texture("levels/level_1.lvl").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use master_smasher::shape::Circle;
+use super::level_data::{Edge, Edges};
+
+use glm;
+
+/// How a body that went past the edges, or through a portal, is brought back to where it
+/// actually is: each edge shifts an axis across the window by wrapping or mirrors it by
+/// bouncing, a portal moves it over to the other end and turns it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fold {
+    linear: glm::DMat2,
+    offset: glm::DVec2,
+    /// What the body's velocity is transformed by.
+    velocity: glm::DMat2,
+}
+
+impl Fold {
+    fn axes(scale: glm::DVec2, offset: glm::DVec2, velocity: glm::DVec2) -> Self {
+        Fold {
+            linear: glm::dmat2(scale.x, 0., 0., scale.y),
+            offset: offset,
+            velocity: glm::dmat2(velocity.x, 0., 0., velocity.y),
+        }
+    }
+
+    /// Takes whatever is around `from` to around `to`, turned by `angle` radians.
+    pub fn turn(angle: f64, from: glm::DVec2, to: glm::DVec2) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let rotation = glm::dmat2(cos, sin, -sin, cos);
+        Fold {
+            linear: rotation,
+            offset: to - rotation * from,
+            velocity: rotation,
+        }
+    }
+
+    /// This fold followed by `next`.
+    pub fn then(&self, next: &Fold) -> Fold {
+        Fold {
+            linear: next.linear * self.linear,
+            offset: next.linear * self.offset + next.offset,
+            velocity: next.velocity * self.velocity,
+        }
+    }
+
+    pub fn apply(&self, point: glm::DVec2) -> glm::DVec2 {
+        self.linear * point + self.offset
+    }
+
+    pub fn deflect(&self, velocity: glm::DVec2) -> glm::DVec2 {
+        self.velocity * velocity
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.linear == glm::dmat2(1., 0., 0., 1.) && self.offset == glm::dvec2(0., 0.)
+    }
+}
+
+/// The window the meteor flies in and what each of its edges does to it.
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    pub size: glm::DVec2,
+    pub edges: Edges,
+}
+
+impl Bounds {
+    pub fn new(size: glm::UVec2, edges: Edges) -> Self {
+        Bounds {
+            size: glm::to_dvec2(size),
+            edges: edges,
+        }
+    }
+
+    /// How to bring `body` back inside, or `None` if it left through an edge that destroys
+    /// it. Wrapping and destroying go by the body's center, bouncing by its outline.
+    pub fn confine(&self, body: &Circle) -> Option<Fold> {
+        let edges = &self.edges;
+        let x = confine_axis(body.center.x, body.radius, self.size.x, edges.left, edges.right)?;
+        let y = confine_axis(body.center.y, body.radius, self.size.y, edges.top, edges.bottom)?;
+        Some(Fold::axes(glm::dvec2(x.0, y.0), glm::dvec2(x.1, y.1), glm::dvec2(x.2, y.2)))
+    }
+
+    /// Offsets at which `body` also shows on the far side of the wrapping edges it overlaps.
+    pub fn ghosts(&self, body: &Circle) -> Vec<glm::DVec2> {
+        let edges = &self.edges;
+        let xs = ghost_axis(body.center.x, body.radius, self.size.x, edges.left, edges.right);
+        let ys = ghost_axis(body.center.y, body.radius, self.size.y, edges.top, edges.bottom);
+        let mut ghosts = vec![];
+        for &x in &xs {
+            for &y in &ys {
+                if x != 0. || y != 0. {
+                    ghosts.push(glm::dvec2(x, y));
+                }
+            }
+        }
+        ghosts
+    }
+}
+
+fn confine_axis(position: f64,
+                radius: f64,
+                size: f64,
+                low: Edge,
+                high: Edge)
+                -> Option<(f64, f64, f64)> {
+    match low {
+        Edge::WRAP if position < 0. => return Some((1., size, 1.)),
+        Edge::BOUNCE { restitution } if position < radius => {
+            return Some((-1., 2. * radius, -restitution));
+        }
+        Edge::DESTROY if position < 0. => return None,
+        _ => {}
+    }
+    match high {
+        Edge::WRAP if position > size => Some((1., -size, 1.)),
+        Edge::BOUNCE { restitution } if position > size - radius => {
+            Some((-1., 2. * (size - radius), -restitution))
+        }
+        Edge::DESTROY if position > size => None,
+        _ => Some((1., 0., 1.)),
+    }
+}
+
+fn ghost_axis(position: f64, radius: f64, size: f64, low: Edge, high: Edge) -> Vec<f64> {
+    let mut offsets = vec![0.];
+    if low == Edge::WRAP && position - radius < 0. {
+        offsets.push(size);
+    }
+    if high == Edge::WRAP && position + radius > size {
+        offsets.push(-size);
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn bounds(left: Edge, right: Edge) -> Bounds {
+        let edges = Edges {
+            left: left,
+            right: right,
+            ..Edges::default()
+        };
+        Bounds::new(glm::uvec2(100, 100), edges)
+    }
+
+    fn circle(x: f64, y: f64) -> Circle {
+        Circle {
+            center: glm::dvec2(x, y),
+            radius: 10.,
+        }
+    }
+
+    #[test]
+    fn wraps_by_center() {
+        let bounds = bounds(Edge::WRAP, Edge::WRAP);
+        assert!(bounds.confine(&circle(5., 50.)).unwrap().is_identity());
+
+        let fold = bounds.confine(&circle(-5., 50.)).unwrap();
+        assert_eq!(fold.apply(glm::dvec2(-5., 50.)), glm::dvec2(95., 50.));
+        assert_eq!(fold.deflect(glm::dvec2(1., 1.)), glm::dvec2(1., 1.));
+    }
+
+    #[test]
+    fn bounces_by_outline() {
+        let bounds = bounds(Edge::WRAP, Edge::BOUNCE { restitution: 0.5 });
+        let fold = bounds.confine(&circle(95., 50.)).unwrap();
+        assert_eq!(fold.apply(glm::dvec2(95., 50.)), glm::dvec2(85., 50.));
+        assert_eq!(fold.deflect(glm::dvec2(1., 1.)), glm::dvec2(-0.5, 1.));
+    }
+
+    #[test]
+    fn destroys_by_center() {
+        let bounds = bounds(Edge::DESTROY, Edge::WRAP);
+        assert!(bounds.confine(&circle(5., 50.)).is_some());
+        assert_eq!(bounds.confine(&circle(-5., 50.)), None);
+    }
+
+    #[test]
+    fn turns_through_portals() {
+        let fold = Fold::turn(FRAC_PI_2, glm::dvec2(10., 10.), glm::dvec2(50., 50.));
+        let moved = fold.apply(glm::dvec2(15., 10.));
+        assert!(glm::distance(moved, glm::dvec2(50., 55.)) < 1e-9);
+        let turned = fold.deflect(glm::dvec2(1., 0.));
+        assert!(glm::distance(turned, glm::dvec2(0., 1.)) < 1e-9);
+
+        let wrap = bounds(Edge::WRAP, Edge::WRAP).confine(&circle(-5., 50.)).unwrap();
+        let both = wrap.then(&fold);
+        assert_eq!(both.apply(glm::dvec2(-5., 50.)), fold.apply(glm::dvec2(95., 50.)));
+    }
+
+    #[test]
+    fn ghosts_only_across_wrapping_edges() {
+        let bounds = bounds(Edge::WRAP, Edge::DESTROY);
+        assert_eq!(bounds.ghosts(&circle(5., 50.)), vec![glm::dvec2(100., 0.)]);
+        assert_eq!(bounds.ghosts(&circle(95., 50.)), vec![]);
+        assert_eq!(bounds.ghosts(&circle(5., 5.)),
+                   vec![glm::dvec2(0., 100.), glm::dvec2(100., 0.), glm::dvec2(100., 100.)]);
+    }
+}
@@ -1,5 +1,6 @@
 use glm;
 use master_smasher::shape::Circle;
+use super::bounds::{Bounds, Fold};
 
 use std::mem;
 
@@ -28,32 +29,39 @@ impl Interpolate for Circle {
 #[derive(Clone)]
 pub struct Wrapped<T: Clone> {
     pub actual: T,
-    pub unwrapped: Option<T>,
-    pub wrapping: glm::DVec2,
+    /// Where it would have been without the edges or portals, and how they brought it here.
+    pub unwrapped: Option<(T, Fold)>,
+    pub bounds: Bounds,
 }
 
 impl Wrapped<Circle> {
-    pub fn displace(&self, displacement: glm::DVec2) -> Wrapped<Circle> {
-        let mut center = self.actual.center + displacement;
-        let mut unwrapped = None;
-        match center {
-            glm::DVec2 { x, y } if (x < 0.) | (y < 0.) | (x > self.wrapping.x) |
-                                   (y > self.wrapping.y) => {
-                unwrapped = Some(Circle {
-                                     center: center,
-                                     radius: self.actual.radius,
-                                 });
-                center = (center + self.wrapping) % self.wrapping;
-            }
-            _ => {}
-        }
+    /// `None` if the body left through an edge that destroys it.
+    pub fn displace(&self, displacement: glm::DVec2) -> Option<Wrapped<Circle>> {
+        let moved = Circle {
+            center: self.actual.center + displacement,
+            radius: self.actual.radius,
+        };
+        let fold = self.bounds.confine(&moved)?;
+        let unwrapped = if fold.is_identity() {
+            None
+        } else {
+            Some((moved, fold))
+        };
+        Some(Wrapped {
+                 actual: Circle { center: fold.apply(moved.center), ..moved },
+                 unwrapped: unwrapped,
+                 bounds: self.bounds,
+             })
+    }
+
+    /// The body put at `center`, folded back inside if that is past an edge.
+    pub fn at(&self, center: glm::DVec2) -> Wrapped<Circle> {
+        let body = Circle { center: center, ..self.actual };
+        let center = self.bounds.confine(&body).map_or(center, |f| f.apply(center));
         Wrapped {
-            actual: Circle {
-                center: center,
-                radius: self.actual.radius,
-            },
-            unwrapped: unwrapped,
-            wrapping: self.wrapping,
+            actual: Circle { center: center, ..body },
+            unwrapped: None,
+            bounds: self.bounds,
         }
     }
 }
@@ -61,18 +69,19 @@ impl Wrapped<Circle> {
 impl<T: Clone + Interpolate> Interpolate for Wrapped<T> {
     fn interpolate(&self, next: &Wrapped<T>, interpolation: f64) -> Wrapped<T> {
         let interpolated = match next.unwrapped {
-            Some(ref c) => self.actual.interpolate(c, interpolation),
+            Some((ref c, _)) => self.actual.interpolate(c, interpolation),
             None => self.actual.interpolate(&next.actual, interpolation),
         };
 
         Wrapped {
             actual: interpolated,
             unwrapped: None,
-            wrapping: self.wrapping,
+            bounds: self.bounds,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct State<T> {
     pub old: T,
     pub current: T,
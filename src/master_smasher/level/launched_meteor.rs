@@ -1,31 +1,44 @@
-use master_smasher::drawable::{Animation, AnimationData, Rectifiable};
-use master_smasher::shape::{Circle, Intersect, Shape};
+use master_smasher::drawable::{Animation, AnimationData, Rectifiable, outline};
+use master_smasher::shape::{Circle, Intersect, Shape, SweptCircle};
+use super::bounds::{Bounds, Fold};
 use super::collidable::Collidable;
 use super::interpolate::*;
-use super::planet::Planet;
+use super::planet::{self, Planet};
+use super::portal::Portal;
 use super::MeteorState;
 use errors::*;
 
 use glm;
 use moho::resource_manager::{Renderer, TextureId};
 use num_traits::One;
+use sdl2::rect;
 
 pub struct LaunchedMeteor {
     body: State<Wrapped<Circle>>,
     texture: TextureId,
     velocity: glm::DVec2,
+    mass: f64,
+    piercing: bool,
+    /// Restitution off planets that don't set their own.
+    bouncy: Option<f64>,
+    /// The portal and end the meteor last came out of, which it cannot go back into before
+    /// leaving it.
+    exit: Option<(usize, usize)>,
 }
 
 impl LaunchedMeteor {
     pub fn new(body: Circle,
                texture: TextureId,
-               max_coords: glm::UVec2,
-               velocity: glm::DVec2)
+               bounds: Bounds,
+               velocity: glm::DVec2,
+               mass: f64,
+               piercing: bool,
+               bouncy: Option<f64>)
                -> Self {
         let wrapped = Wrapped {
             actual: body,
             unwrapped: None,
-            wrapping: glm::to_dvec2(max_coords),
+            bounds: bounds,
         };
 
         let body = State::new(wrapped);
@@ -34,25 +47,153 @@ impl LaunchedMeteor {
             texture: texture,
             body: body,
             velocity: velocity,
+            mass: mass,
+            piercing: piercing,
+            bouncy: bouncy,
+            exit: None,
         }
     }
 
-    pub fn update(&mut self, planets: &[Planet]) {
+    /// Moves the meteor for one tick, returning whether it crashed into a planet on the way
+    /// or left through an edge that destroys it. A crashed meteor is left at the point of
+    /// impact, a bounced one at the point it bounced from for the rest of the tick.
+    pub fn update(&mut self, planets: &[Planet], portals: &[Portal]) -> bool {
         self.velocity = self.velocity + self.acceleration(planets);
-        self.displace();
+        if !self.displace() {
+            return true;
+        }
+
+        let path = self.path();
+        let impact = planets.iter()
+            .flat_map(|p| path.iter().filter_map(move |s| p.impact(s).map(|t| (t, p))))
+            .fold(None, |earliest: Option<(f64, &Planet)>, (t, p)| {
+                match earliest {
+                    Some((e, _)) if e <= t => earliest,
+                    _ => Some((t, p)),
+                }
+            });
+
+        if let Some((t, planet)) = impact {
+            self.cut(t);
+            match planet.bouncy.or(self.bouncy) {
+                Some(restitution) => self.bounce(planet, restitution),
+                None => return true,
+            }
+        }
+        self.pass(portals);
+        false
+    }
+
+    /// Stops the tick's movement at `t`. The segments of the path share their timing, so the
+    /// meteor is moved that far along the unwrapped one and keeps its fold if it got past the edge.
+    fn cut(&mut self, t: f64) {
+        let end = self.path()[0].at(t);
+        let old = &self.body.old;
+        self.body.current = old.displace(end - old.actual.center)
+            .unwrap_or_else(|| self.body.current.at(end));
+    }
+
+    /// Sends the meteor through the first portal end its center went into during the tick.
+    fn pass(&mut self, portals: &[Portal]) {
+        let path = self.path();
+        let exit = self.exit;
+        let entry = portals.iter()
+            .enumerate()
+            .flat_map(|(i, p)| (0..2).map(move |e| (i, e, p)))
+            .filter(|&(i, e, _)| exit != Some((i, e)))
+            .flat_map(|(i, e, p)| path.iter().filter_map(move |s| p.entry(e, s).map(|t| (t, i, e))))
+            .fold(None, |earliest: Option<(f64, usize, usize)>, (t, i, e)| {
+                match earliest {
+                    Some((f, _, _)) if f <= t => earliest,
+                    _ => Some((t, i, e)),
+                }
+            });
+
+        match entry {
+            Some((_, i, e)) => {
+                self.teleport(portals[i].fold(e));
+                self.exit = Some((i, 1 - e));
+            }
+            None => {
+                if let Some((i, e)) = self.exit {
+                    if !portals[i].covers(e, self.center()) {
+                        self.exit = None;
+                    }
+                }
+            }
+        }
     }
 
+    /// Moves the meteor to where `fold` takes it. Until the next tick it is drawn going on into
+    /// the portal rather than sliding across to the other end.
+    fn teleport(&mut self, fold: Fold) {
+        let current = self.body.current.clone();
+        let unwrapped = match current.unwrapped {
+            Some((u, f)) => (u, f.then(&fold)),
+            None => (current.actual, fold),
+        };
+        let moved = current.at(fold.apply(current.actual.center));
+        self.body.current = Wrapped { unwrapped: Some(unwrapped), ..moved };
+        self.velocity = fold.deflect(self.velocity);
+    }
+
+    /// Pushes the meteor out of `planet` and reflects the part of its velocity going into it.
+    fn bounce(&mut self, planet: &Planet, restitution: f64) {
+        let body = self.body.current.actual;
+        if let Some(contact) = planet.contact(&body) {
+            let pushed = self.body.current.at(body.center + contact.normal * contact.depth);
+            self.body.current = Wrapped { unwrapped: self.body.current.unwrapped, ..pushed };
+            let into = glm::dot(self.velocity, contact.normal);
+            if into < 0. {
+                self.velocity = self.velocity - contact.normal * (into * (1. + restitution));
+            }
+        }
+    }
+
+    /// Also draws whatever part of the meteor is past a wrapping edge on the other side.
     pub fn draw<R: Renderer>(&self, interpolation: f64, renderer: &mut R) -> Result<()> {
         let body = self.body.interpolated(interpolation).actual;
-        renderer.render(&self.texture, body.rectify()).map_err(Into::into)
+        renderer.render(&self.texture, body.rectify())?;
+        for offset in self.body.current.bounds.ghosts(&body) {
+            let ghost = Circle { center: body.center + offset, ..body };
+            renderer.render(&self.texture, ghost.rectify())?;
+        }
+        Ok(())
+    }
+
+    /// The body plus where the velocity and the planets' pull are taking it, both scaled up
+    /// to be visible.
+    pub fn outline(&self, planets: &[Planet], rects: &mut Vec<rect::Rect>) {
+        const VELOCITY_SCALE: f64 = 10.;
+        const ACCELERATION_SCALE: f64 = 200.;
+
+        let body = self.body.current.actual;
+        outline::circle(&body, rects);
+        outline::segment(body.center, body.center + self.velocity * VELOCITY_SCALE, rects);
+        outline::segment(body.center,
+                         body.center + self.acceleration(planets) * ACCELERATION_SCALE,
+                         rects);
+    }
+
+    pub fn push(&mut self, delta_velocity: glm::DVec2) {
+        self.velocity = self.velocity + delta_velocity;
+    }
+
+    /// Whether it flies on through the stars and enemies it destroys.
+    pub fn is_piercing(&self) -> bool {
+        self.piercing
+    }
+
+    pub fn center(&self) -> glm::DVec2 {
+        self.body.current.actual.center
     }
 
     pub fn collides<S, C>(&self, collidable: &C) -> bool
         where S: Shape,
-              C: Collidable<S, Circle>,
-              Circle: Intersect<S>
+              C: Collidable<S, SweptCircle>,
+              SweptCircle: Intersect<S>
     {
-        collidable.collides(&self.body.current.actual)
+        self.path().iter().any(|s| collidable.collides(s))
     }
 
     pub fn explode(&self, explosion: AnimationData) -> MeteorState {
@@ -65,12 +206,213 @@ impl LaunchedMeteor {
     }
 
     fn acceleration(&self, planets: &[Planet]) -> glm::DVec2 {
-        let body = self.body.current.actual;
-        planets.iter().map(|p| p.pull_vector(&body)).fold(glm::dvec2(0., 0.), |c, a| c + a) / 50.
+        planet::acceleration(planets, &self.body.current.actual, self.mass)
+    }
+
+    /// The movement of the last tick; a movement past an edge or through a portal is also
+    /// swept from where that folds the start of it to: on the far side for a wrap, mirrored for
+    /// a bounce or at the other end of the portal.
+    fn path(&self) -> Vec<SweptCircle> {
+        let old = self.body.old.actual;
+        let current = &self.body.current;
+        let end = current.unwrapped.map_or(current.actual, |(u, _)| u);
+        let swept = SweptCircle {
+            circle: old,
+            displacement: end.center - old.center,
+        };
+
+        match current.unwrapped {
+            None => vec![swept],
+            Some((_, fold)) => {
+                let start = fold.apply(old.center);
+                let folded = SweptCircle {
+                    circle: Circle { center: start, ..old },
+                    displacement: current.actual.center - start,
+                };
+                vec![swept, folded]
+            }
+        }
+    }
+
+    /// Returns false if the meteor left through an edge that destroys it.
+    fn displace(&mut self) -> bool {
+        match self.body.current.displace(self.velocity) {
+            Some(next) => {
+                if let Some((_, fold)) = next.unwrapped {
+                    self.velocity = fold.deflect(self.velocity);
+                }
+                self.body.update(next);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::HeadlessLoader;
+    use master_smasher::level::level_data::{Edge, Edges, Gravity, ObjectData, PlanetData,
+                                            PlanetKind, PortalData};
+    use master_smasher::level::world_assets::WorldAssets;
+    use moho::resource_manager::TextureId;
+
+    fn dead_planet(x: i32, y: i32) -> Planet {
+        let assets = WorldAssets::new(&HeadlessLoader::default()).unwrap();
+        let data = PlanetData {
+            x: x,
+            y: y,
+            kind: PlanetKind::DEAD,
+            gravity: Gravity::default(),
+            bouncy: None,
+            motion: None,
+        };
+        Planet::new(&data, &assets)
+    }
+
+    fn bouncy_planet(x: i32, y: i32, restitution: f64) -> Planet {
+        let mut planet = dead_planet(x, y);
+        planet.bouncy = Some(restitution);
+        planet
+    }
+
+    fn portal(rotation: f64) -> Portal {
+        let assets = WorldAssets::new(&HeadlessLoader::default()).unwrap();
+        let data = PortalData {
+            a: ObjectData { x: 300, y: 100 },
+            b: ObjectData { x: 800, y: 400 },
+            rotation: rotation,
+        };
+        Portal::new(&data, assets.portal)
+    }
+
+    fn bounds(edges: Edges) -> Bounds {
+        Bounds::new(glm::uvec2(1280, 720), edges)
+    }
+
+    fn meteor(center: glm::DVec2, velocity: glm::DVec2) -> LaunchedMeteor {
+        let body = Circle {
+            center: center,
+            radius: 30.,
+        };
+        LaunchedMeteor::new(body, TextureId(0), bounds(Edges::default()), velocity, 50., true, None)
+    }
+
+    #[test]
+    fn fast_meteor_does_not_tunnel() {
+        let planets = [dead_planet(300, 100)];
+        let mut meteor = meteor(glm::dvec2(100., 100.), glm::dvec2(400., 0.));
+        assert!(meteor.update(&planets, &[]));
+        assert_eq!(meteor.center(), glm::dvec2(300. - 37.5 - 30., 100.));
+    }
+
+    #[test]
+    fn crashes_across_wrap() {
+        let planets = [dead_planet(50, 100)];
+        let mut meteor = meteor(glm::dvec2(1200., 100.), glm::dvec2(200., 0.));
+        assert!(meteor.update(&planets, &[]));
+        assert_eq!(meteor.center(), glm::dvec2(1280. + 50. - 37.5 - 30., 100.));
+    }
+
+    #[test]
+    fn misses_planets() {
+        let planets = [dead_planet(300, 300)];
+        let mut meteor = meteor(glm::dvec2(100., 100.), glm::dvec2(400., 0.));
+        assert!(!meteor.update(&planets, &[]));
+        assert_eq!(meteor.center(), glm::dvec2(500., 100.));
+    }
+
+    #[test]
+    fn bounces_off_edges() {
+        let edges = Edges { right: Edge::BOUNCE { restitution: 0.5 }, ..Edges::default() };
+        let mut meteor = LaunchedMeteor::new(Circle {
+                                                 center: glm::dvec2(1240., 100.),
+                                                 radius: 30.,
+                                             },
+                                             TextureId(0),
+                                             bounds(edges),
+                                             glm::dvec2(20., 0.),
+                                             50.,
+                                             true,
+                                             None);
+        assert!(!meteor.update(&[], &[]));
+        assert_eq!(meteor.center(), glm::dvec2(1240., 100.));
+        assert_eq!(meteor.velocity, glm::dvec2(-10., 0.));
+    }
+
+    #[test]
+    fn destroyed_leaving_the_window() {
+        let edges = Edges { bottom: Edge::DESTROY, ..Edges::default() };
+        let mut meteor = LaunchedMeteor::new(Circle {
+                                                 center: glm::dvec2(100., 710.),
+                                                 radius: 30.,
+                                             },
+                                             TextureId(0),
+                                             bounds(edges),
+                                             glm::dvec2(0., 20.),
+                                             50.,
+                                             true,
+                                             None);
+        assert!(meteor.update(&[], &[]));
+    }
+
+    #[test]
+    fn bounces_off_bouncy_planets() {
+        let planets = [bouncy_planet(300, 100, 0.5)];
+        let mut meteor = meteor(glm::dvec2(100., 100.), glm::dvec2(400., 0.));
+        assert!(!meteor.update(&planets, &[]));
+        assert_eq!(meteor.center(), glm::dvec2(300. - 37.5 - 30., 100.));
+        assert_eq!(meteor.velocity, glm::dvec2(-200., 0.));
+    }
+
+    #[test]
+    fn bouncy_meteors_bounce_off_any_planet() {
+        let planets = [dead_planet(300, 100)];
+        let mut meteor = meteor(glm::dvec2(100., 100.), glm::dvec2(400., 0.));
+        meteor.bouncy = Some(0.5);
+        assert!(!meteor.update(&planets, &[]));
+        assert_eq!(meteor.velocity, glm::dvec2(-200., 0.));
+    }
+
+    #[test]
+    fn oblique_bounce_gets_clear() {
+        let planets = [bouncy_planet(300, 100, 1.)];
+        let mut meteor = meteor(glm::dvec2(150., 83.), glm::dvec2(7.3, 3.1));
+        for _ in 0..40 {
+            let before = meteor.center();
+            assert!(!meteor.update(&planets, &[]));
+            assert!(meteor.center() != before);
+        }
+        assert!(glm::distance(meteor.center(), glm::dvec2(300., 100.)) > 37.5 + 30.);
+    }
+
+    #[test]
+    fn elastic_glancing_bounce_keeps_speed() {
+        let planets = [bouncy_planet(300, 100, 1.)];
+        let mut meteor = meteor(glm::dvec2(280., 0.), glm::dvec2(10., 40.));
+        assert!(!meteor.update(&planets, &[]));
+        assert!(meteor.velocity.x < 0. && meteor.velocity.y < 0.);
+        assert!((glm::length(meteor.velocity) - glm::length(glm::dvec2(10., 40.))).abs() < 1e-9);
+    }
+
+    #[test]
+    fn goes_through_portals_without_smearing() {
+        let portals = [portal(90.)];
+        let mut meteor = meteor(glm::dvec2(200., 100.), glm::dvec2(150., 0.));
+        assert!(!meteor.update(&[], &portals));
+        assert!(glm::distance(meteor.center(), glm::dvec2(800., 450.)) < 1e-9);
+        assert!(glm::distance(meteor.velocity, glm::dvec2(0., 150.)) < 1e-9);
+        assert_eq!(meteor.body.interpolated(0.5).actual.center, glm::dvec2(275., 100.));
     }
 
-    fn displace(&mut self) {
-        let next = self.body.current.displace(self.velocity);
-        self.body.update(next);
+    #[test]
+    fn does_not_go_straight_back() {
+        let portals = [portal(90.)];
+        let mut meteor = meteor(glm::dvec2(280., 100.), glm::dvec2(10., 0.));
+        assert!(!meteor.update(&[], &portals));
+        assert!(glm::distance(meteor.center(), glm::dvec2(800., 390.)) < 1e-9);
+        assert!(!meteor.update(&[], &portals));
+        assert!(glm::distance(meteor.center(), glm::dvec2(800., 400.)) < 1e-9);
     }
 }
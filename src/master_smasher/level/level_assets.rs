@@ -0,0 +1,51 @@
+use master_smasher::drawable::Animation;
+use super::level_data::PlanetData;
+use super::planet::Planet;
+use super::player_assets::PlayerAssets;
+use super::world_assets::WorldAssets;
+
+use glm;
+use moho::errors::*;
+use moho::resource_manager::ResourceLoader;
+
+use std::cmp;
+
+#[derive(Clone)]
+pub struct LevelAssets {
+    pub player: PlayerAssets,
+    pub world: WorldAssets,
+}
+
+impl LevelAssets {
+    pub fn new<L: ResourceLoader>(resource_loader: &L) -> Result<Self> {
+        let assets = LevelAssets {
+            player: PlayerAssets::new(resource_loader)?,
+            world: WorldAssets::new(resource_loader)?,
+        };
+        Ok(assets)
+    }
+
+    /// Radii of the bodies' sprites, for laying levels out without building them.
+    pub fn meteor_radius(&self) -> f64 {
+        let dims = self.player.meteor.dims;
+        cmp::min(dims.x, dims.y) as f64 / 2.
+    }
+
+    pub fn planet_radius(&self, data: &PlanetData) -> f64 {
+        Planet::radius(data, &self.world)
+    }
+
+    pub fn star_radius(&self) -> f64 {
+        Self::animation_radius(self.world.star(glm::ivec2(0, 0)))
+    }
+
+    pub fn enemy_radius(&self) -> f64 {
+        Self::animation_radius(self.world.enemy(glm::ivec2(0, 0)))
+    }
+
+    /// Stars and enemies are hit as rectangles, so this reaches their corners.
+    fn animation_radius(animation: Animation) -> f64 {
+        let rect = animation.dst_rect;
+        glm::length(glm::dvec2(rect.z as f64, rect.w as f64)) / 2.
+    }
+}
@@ -1,19 +1,23 @@
+use master_smasher::save;
 use errors::*;
 
 use glm;
 use serde_yaml;
 
 use std::fs::File;
+use std::path::Path;
 
-#[derive(Debug,Deserialize)]
+#[derive(Clone,Debug,Serialize,Deserialize)]
 pub enum PlanetKind {
     RED { ring: f64, strength: f64 },
     BLUE { ring: f64, strength: f64 },
     WHITE { ring: f64, strength: f64 },
+    /// A repulsor: its ring pushes the meteor away instead of pulling it in.
+    PURPLE { ring: f64, strength: f64 },
     DEAD,
 }
 
-#[derive(Debug,Deserialize)]
+#[derive(Clone,Debug,Serialize,Deserialize)]
 pub struct ObjectData {
     pub x: i32,
     pub y: i32,
@@ -25,24 +29,296 @@ impl<'a> From<&'a ObjectData> for glm::IVec2 {
     }
 }
 
-#[derive(Debug,Deserialize)]
+/// How hard a ring pulls at a given distance from its planet, `strength` scaling each law.
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+pub enum Gravity {
+    /// `strength / distance^exponent`; the default law has an exponent of 0.8.
+    POWER { exponent: f64 },
+    /// `strength / distance^2`
+    SQUARE,
+    /// `strength` everywhere inside the ring.
+    CONSTANT,
+    /// `strength` at the planet's center, falling off to nothing at the ring.
+    LINEAR,
+    /// Pushes away with `strength / distance^exponent`.
+    REPULSIVE { exponent: f64 },
+}
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Gravity::POWER { exponent: 0.8 }
+    }
+}
+
+impl Gravity {
+    pub fn is_default(&self) -> bool {
+        *self == Gravity::default()
+    }
+}
+
+/// How a planet moves, starting from its own position.
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub enum Motion {
+    /// Circles (x, y) once every `period` ticks; a negative period circles the other way.
+    ORBIT { x: i32, y: i32, period: f64 },
+    /// Follows `points` and then returns to where it started, `speed` pixels per tick.
+    PATH { points: Vec<ObjectData>, speed: f64 },
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
 pub struct PlanetData {
     pub x: i32,
     pub y: i32,
     pub kind: PlanetKind,
+    #[serde(default, skip_serializing_if = "Gravity::is_default")]
+    pub gravity: Gravity,
+    /// The meteor bounces off instead of crashing, keeping this much of its speed into the
+    /// planet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bouncy: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motion: Option<Motion>,
+}
+
+/// Two linked portals: the meteor flying into either one comes out of the other.
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct PortalData {
+    pub a: ObjectData,
+    pub b: ObjectData,
+    /// Degrees clockwise the meteor's velocity is turned going from `a` to `b`; going back
+    /// turns it the other way.
+    #[serde(default)]
+    pub rotation: f64,
 }
 
-#[derive(Debug,Deserialize)]
+/// What a meteor is, which sets its sprite and how it flies unless a level says otherwise.
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+pub enum MeteorKind {
+    ROCK,
+    /// Bent less by the planets, but launched slower.
+    HEAVY,
+    /// Bent more by the planets.
+    LIGHT,
+    /// Flies on through the stars and enemies it destroys.
+    PIERCING,
+    /// Bounces off planets instead of crashing into them.
+    BOUNCY,
+}
+
+impl Default for MeteorKind {
+    fn default() -> Self {
+        MeteorKind::ROCK
+    }
+}
+
+/// One meteor a level hands out. Anything left out is what its kind has.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Serialize,Deserialize)]
+pub struct MeteorType {
+    #[serde(default)]
+    pub kind: MeteorKind,
+    /// Multiplies `physics.mass`, so heavier meteors are bent less by the planets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mass: Option<f64>,
+    /// Multiplies the launch velocity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f64>,
+    /// Flies on through stars and enemies instead of exploding on the first one it destroys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub piercing: Option<bool>,
+    /// Bounces off any planet, keeping this much of its speed into it, unless the planet
+    /// sets its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bouncy: Option<f64>,
+}
+
+impl MeteorType {
+    pub fn mass(&self) -> f64 {
+        self.mass.unwrap_or(match self.kind {
+                                MeteorKind::HEAVY => 2.,
+                                MeteorKind::LIGHT => 0.5,
+                                _ => 1.,
+                            })
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed.unwrap_or(match self.kind {
+                                 MeteorKind::HEAVY => 0.7,
+                                 _ => 1.,
+                             })
+    }
+
+    pub fn piercing(&self) -> bool {
+        self.piercing.unwrap_or(self.kind == MeteorKind::PIERCING)
+    }
+
+    pub fn bouncy(&self) -> Option<f64> {
+        const RESTITUTION: f64 = 0.8;
+        match self.kind {
+            MeteorKind::BOUNCY => Some(self.bouncy.unwrap_or(RESTITUTION)),
+            _ => self.bouncy,
+        }
+    }
+}
+
+/// Constants of the whole level's physics.
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+#[serde(default)]
+pub struct Physics {
+    /// What the planets' combined pull is divided by to accelerate the meteor.
+    pub mass: f64,
+    /// What the distance from the meteor to the aim point is divided by to launch it.
+    pub launch: f64,
+    /// What a tick of thrust adds to the meteor's speed.
+    pub thrust: f64,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Physics {
+            mass: 50.,
+            launch: 50.,
+            thrust: 0.1,
+        }
+    }
+}
+
+impl Physics {
+    pub fn is_default(&self) -> bool {
+        *self == Physics::default()
+    }
+}
+
+/// What happens to the meteor at an edge of the window.
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+pub enum Edge {
+    /// Comes back in through the opposite edge.
+    WRAP,
+    /// Bounces off, keeping `restitution` of its speed across the edge.
+    BOUNCE { restitution: f64 },
+    /// Explodes as soon as its center leaves the window.
+    DESTROY,
+}
+
+impl Default for Edge {
+    fn default() -> Self {
+        Edge::WRAP
+    }
+}
+
+/// Edges left out of a level wrap.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Serialize,Deserialize)]
+pub struct Edges {
+    #[serde(default)]
+    pub left: Edge,
+    #[serde(default)]
+    pub right: Edge,
+    #[serde(default)]
+    pub top: Edge,
+    #[serde(default)]
+    pub bottom: Edge,
+}
+
+impl Edges {
+    pub fn all_wrap(&self) -> bool {
+        *self == Edges::default()
+    }
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
 pub struct LevelData {
     pub meteor: ObjectData,
     pub stars: Vec<ObjectData>,
     pub enemies: Vec<ObjectData>,
+    /// Launch stations the shots after the first start from in turn, going back to the
+    /// meteor's own position after the last one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stations: Vec<ObjectData>,
+    /// The meteors handed out, one per shot, the player picking which goes next. Left out,
+    /// every shot is a plain meteor.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub meteors: Vec<MeteorType>,
     pub planets: Vec<PlanetData>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub portals: Vec<PortalData>,
+    #[serde(default, skip_serializing_if = "Edges::all_wrap")]
+    pub edges: Edges,
+    #[serde(default, skip_serializing_if = "Physics::is_default")]
+    pub physics: Physics,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shots: Option<u32>,
+    /// Ticks of thrust the player can spend steering the meteor in flight, across all shots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u32>,
 }
 
 impl LevelData {
-    pub fn load(path: &'static str) -> Result<LevelData> {
-        let f = File::open(path)?;
-        Ok(serde_yaml::from_reader(&f)?)
+    /// Number of ticks the aiming preview follows the launch for.
+    pub fn preview_steps(&self) -> u32 {
+        const DEFAULT_PREVIEW: u32 = 90;
+        self.preview.unwrap_or(DEFAULT_PREVIEW)
+    }
+
+    /// Where each shot starts from, the first one being the meteor's own position.
+    pub fn starts(&self) -> Vec<glm::IVec2> {
+        let mut starts = vec![(&self.meteor).into()];
+        starts.extend(self.stations.iter().map(glm::IVec2::from));
+        starts
+    }
+
+    /// How many shots the level allows, if it limits them at all.
+    pub fn max_shots(&self) -> Option<u32> {
+        let meteors = self.meteors.len() as u32;
+        match self.shots {
+            Some(s) if meteors > 0 => Some(s.min(meteors)),
+            None if meteors > 0 => Some(meteors),
+            shots => shots,
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<LevelData> {
+        let path = path.as_ref();
+        let f = File::open(path).chain_err(|| format!("could not open {}", path.display()))?;
+        serde_yaml::from_reader(&f).chain_err(|| format!("could not parse {}", path.display()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let yaml = serde_yaml::to_string(self)?;
+        save::write_atomically(path, yaml.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn round_trips_through_file() {
+        let dir = env::temp_dir().join(format!("master_smasher_level_{}", process::id()));
+        let path = dir.join("level.lvl");
+        let data = LevelData::load("levels/level_2.lvl").unwrap();
+        data.save(&path).unwrap();
+        let saved = LevelData::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(serde_yaml::to_string(&saved).unwrap(),
+                   serde_yaml::to_string(&data).unwrap());
+    }
+
+    #[test]
+    fn kinds_have_their_own_defaults() {
+        let meteors: Vec<MeteorType> =
+            serde_yaml::from_str("[{}, {kind: HEAVY}, {kind: PIERCING}, {kind: BOUNCY, mass: 3}]")
+                .unwrap();
+        let (rock, heavy, piercing, bouncy) = (meteors[0], meteors[1], meteors[2], meteors[3]);
+        assert!(!rock.piercing() && rock.bouncy().is_none());
+        assert!(heavy.mass() > rock.mass() && heavy.speed() < rock.speed());
+        assert!(piercing.piercing());
+        assert!(bouncy.bouncy().is_some() && !bouncy.piercing());
+        assert_eq!(bouncy.mass(), 3.);
     }
 }
@@ -1,74 +1,402 @@
+mod bounds;
 mod unlaunched_meteor;
 mod launched_meteor;
 mod star;
 mod planet;
+mod portal;
+mod level_assets;
 mod level_data;
 mod collidable;
 mod player;
 mod player_assets;
+mod player_input;
+mod simulation;
 mod world;
 mod world_assets;
 mod interpolate;
+mod outcome;
+mod validation;
 
-use self::level_data::LevelData;
+pub use self::level_assets::LevelAssets;
+pub use self::level_data::{Edges, Gravity, LevelData, ObjectData, Physics, PlanetData,
+                           PlanetKind};
+pub use self::outcome::{Outcome, Score};
+pub use self::player_input::PlayerInput;
+pub use self::simulation::Simulation;
+pub use self::validation::{Diagnostic, Severity, load_valid, validate};
+
+use self::bounds::Bounds;
+use self::collidable::Collidable;
+use self::level_data::MeteorKind;
 use self::player::{MeteorState, Player};
-use self::player_assets::PlayerAssets;
 use self::world::World;
-use self::world_assets::WorldAssets;
+use master_smasher::drawable::outline;
+use master_smasher::shape::Circle;
 use errors::*;
 
 use glm;
-use moho::input_manager::{EventPump, InputManager};
-use moho::resource_manager::{Renderer, ResourceLoader};
+use glm::ext::normalize_to;
+use moho::resource_manager::Renderer;
+use sdl2::rect;
 
+use std::cmp;
 use std::time::Duration;
 
+/// Debug layers drawn over a level.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Overlay {
+    /// Every collision shape and the forces on the meteor.
+    pub shapes: bool,
+    /// The planets' combined pull sampled across the window.
+    pub field: bool,
+}
+
+/// Something placed in a level, by its index in the `LevelData` lists.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Body {
+    METEOR,
+    PLANET(usize),
+    STAR(usize),
+    ENEMY(usize),
+}
+
 pub struct Level {
     world: World,
     player: Player,
+    total_stars: u32,
+    max_shots: Option<u32>,
+    ticks: u32,
+    data: LevelData,
+    assets: LevelAssets,
+    window_size: glm::UVec2,
 }
 
 impl Level {
-    pub fn load<L>(path: &'static str, size: glm::UVec2, resource_loader: &L) -> Result<Level>
-        where L: ResourceLoader
-    {
-        let data = LevelData::load(path)?;
-        let player_assets = PlayerAssets::new(resource_loader)?;
-        let world_assets = WorldAssets::new(resource_loader)?;
-        Ok(Level::new(data, size, player_assets, world_assets))
-    }
-
-    pub fn new(data: LevelData,
-               window_size: glm::UVec2,
-               player_assets: PlayerAssets,
-               world_assets: WorldAssets)
-               -> Level {
-        let world = World::new(&data, world_assets);
-        let player = Player::new(player_assets, (&data.meteor).into(), window_size);
-
+    pub fn new(data: LevelData, window_size: glm::UVec2, assets: LevelAssets) -> Level {
+        let (world, player) = Level::start(&data, window_size, &assets);
         Level {
             world: world,
             player: player,
+            total_stars: data.stars.len() as u32,
+            max_shots: data.max_shots(),
+            ticks: 0,
+            data: data,
+            assets: assets,
+            window_size: window_size,
+        }
+    }
+
+    /// Puts back every star and enemy and starts over from the first shot.
+    pub fn restart(&mut self) {
+        let (world, player) = Level::start(&self.data, self.window_size, &self.assets);
+        self.world = world;
+        self.player = player;
+        self.ticks = 0;
+    }
+
+    fn start(data: &LevelData, window_size: glm::UVec2, assets: &LevelAssets) -> (World, Player) {
+        let world = World::new(data, assets.world.clone());
+        let player = Player::new(assets.player.clone(),
+                                 data.starts(),
+                                 if data.meteors.is_empty() {
+                                     None
+                                 } else {
+                                     Some(data.meteors.clone())
+                                 },
+                                 Bounds::new(window_size, data.edges),
+                                 data.physics,
+                                 data.fuel.unwrap_or(0),
+                                 data.preview_steps());
+        (world, player)
+    }
+
+    pub fn update(&mut self, input: &PlayerInput) {
+        if input.restart {
+            self.restart();
+            return;
+        }
+        if self.outcome().is_some() {
+            return;
+        }
+
+        self.ticks += 1;
+        self.world.update();
+        let crashed = self.player.update(&self.world.planets, &self.world.portals, input);
+
+        // a crashed meteor still destroys what it passed on its way into the planet
+        let hit = match self.player.state {
+            MeteorState::LAUNCHED(ref m) => self.world.collide(m) && !m.is_piercing(),
+            _ => false,
+        };
+        if crashed || hit {
+            self.player.crash();
         }
     }
 
-    pub fn update<E: EventPump>(&mut self, input_manager: &InputManager<E>) {
-        self.player.update(&self.world.planets, input_manager);
+    /// A level is cleared once every enemy is destroyed, and failed when its last shot is over
+    /// with enemies left.
+    pub fn outcome(&self) -> Option<Outcome> {
+        let out_of_shots = self.max_shots.map_or(false, |m| self.player.shots >= m);
+        if self.world.enemies.is_empty() {
+            Some(Outcome::CLEARED)
+        } else if out_of_shots && self.player.is_ready() {
+            Some(Outcome::FAILED)
+        } else {
+            None
+        }
+    }
 
-        if let MeteorState::LAUNCHED(ref m) = self.player.state {
-            self.world.collide(m);
+    pub fn score(&self) -> Score {
+        Score {
+            shots: self.player.shots,
+            stars: self.total_stars - self.world.stars.len() as u32,
+            total_stars: self.total_stars,
+            ticks: self.ticks,
         }
     }
 
+    /// The body under `point`, topmost first. Indices only match the `LevelData` until
+    /// something is destroyed.
+    pub fn body_at(&self, point: glm::IVec2) -> Option<Body> {
+        let point = Circle {
+            center: glm::to_dvec2(point),
+            radius: 0.,
+        };
+        if let MeteorState::UNLAUNCHED(ref m) = self.player.state {
+            if m.collides(&point) {
+                return Some(Body::METEOR);
+            }
+        }
+
+        let world = &self.world;
+        world.stars
+            .iter()
+            .position(|s| s.collides(&point))
+            .map(Body::STAR)
+            .or_else(|| world.enemies.iter().position(|e| e.collides(&point)).map(Body::ENEMY))
+            .or_else(|| world.planets.iter().position(|p| p.collides(&point)).map(Body::PLANET))
+    }
+
     pub fn animate(&mut self, delta: Duration) {
-        self.player.animate(delta);
         self.world.animate(delta);
     }
 
     pub fn draw<R>(&self, interpolation: f64, renderer: &mut R) -> Result<()>
         where R: Renderer
     {
-        renderer.show(&self.world)?;
-        self.player.draw(interpolation, renderer)
+        self.world.draw(interpolation, renderer)?;
+        self.player.draw(interpolation, renderer)?;
+        self.draw_shots(renderer)?;
+        self.draw_fuel(renderer)
+    }
+
+    /// A bar in the bottom right corner emptying as the fuel is spent, if there is any.
+    fn draw_fuel<R: Renderer>(&self, renderer: &mut R) -> Result<()> {
+        const WIDTH: u32 = 200;
+        const HEIGHT: u32 = 12;
+        const MARGIN: i32 = 10;
+        const BORDER: u32 = 2;
+
+        let total = match self.data.fuel {
+            Some(f) if f > 0 => f,
+            _ => return Ok(()),
+        };
+        let x = self.window_size.x as i32 - WIDTH as i32 - MARGIN;
+        let y = self.window_size.y as i32 - HEIGHT as i32 - MARGIN;
+        let inner = WIDTH - 4 * BORDER;
+        let left = (inner as u64 * self.player.fuel as u64 / total as u64) as u32;
+        let b = BORDER as i32;
+        let mut rects = vec![rect::Rect::new(x, y, WIDTH, BORDER),
+                             rect::Rect::new(x, y + HEIGHT as i32 - b, WIDTH, BORDER),
+                             rect::Rect::new(x, y, BORDER, HEIGHT),
+                             rect::Rect::new(x + WIDTH as i32 - b, y, BORDER, HEIGHT)];
+        if left > 0 {
+            rects.push(rect::Rect::new(x + 2 * b, y + 2 * b, left, HEIGHT - 4 * BORDER));
+        }
+        renderer.fill_rects(&rects).map_err(Into::into)
+    }
+
+    /// A small meteor in the bottom left corner for every shot left, if they are limited, the
+    /// selected one raised when there is a choice.
+    fn draw_shots<R: Renderer>(&self, renderer: &mut R) -> Result<()> {
+        const SIZE: i32 = 24;
+        const MARGIN: i32 = 10;
+        const RAISE: i32 = 6;
+
+        let left = match self.max_shots {
+            Some(m) => m.saturating_sub(self.player.shots),
+            None => return Ok(()),
+        };
+        let ammo = self.player.ammo.as_ref();
+        let choosing = ammo.map_or(false, |a| a.len() > 1);
+        let y = self.window_size.y as i32 - SIZE - MARGIN;
+        for i in 0..left as usize {
+            let kind = ammo.and_then(|a| a.get(i)).map_or(MeteorKind::ROCK, |m| m.kind);
+            let raise = if choosing && i == self.player.selected { RAISE } else { 0 };
+            let x = MARGIN + i as i32 * (SIZE + MARGIN / 2);
+            let rect = glm::ivec4(x, y - raise, SIZE, SIZE);
+            renderer.render(&self.assets.player.meteor(kind).id, rect)?;
+        }
+        Ok(())
+    }
+
+    pub fn draw_overlay<R: Renderer>(&self, overlay: Overlay, renderer: &mut R) -> Result<()> {
+        let mut rects = vec![];
+        if overlay.field {
+            self.field(&mut rects);
+        }
+        if overlay.shapes {
+            self.world.outline(&mut rects);
+            self.player.outline(&self.world.planets, &mut rects);
+        }
+        renderer.fill_rects(&rects).map_err(Into::into)
+    }
+
+    /// The acceleration a meteor centered on `point` would get from the planets.
+    pub fn field_at(&self, point: glm::DVec2) -> glm::DVec2 {
+        let meteor = self.assets.player.meteor.dims;
+        let body = Circle {
+            center: point,
+            radius: cmp::min(meteor.x, meteor.y) as f64 / 2.,
+        };
+        planet::acceleration(&self.world.planets, &body, self.data.physics.mass)
+    }
+
+    /// An arrow on every grid point that feels a pull, longer where it pulls harder.
+    fn field(&self, rects: &mut Vec<rect::Rect>) {
+        const STEP: u32 = 40;
+        const SCALE: f64 = 100.;
+
+        let max_length = STEP as f64 * 0.8;
+        for y in 0..self.window_size.y / STEP {
+            for x in 0..self.window_size.x / STEP {
+                let point = glm::to_dvec2(glm::uvec2(x, y) * STEP + STEP / 2);
+                let acceleration = self.field_at(point);
+                let strength = glm::length(acceleration);
+                if strength > 0. {
+                    let length = (strength * SCALE).min(max_length);
+                    outline::arrow(point, point + normalize_to(acceleration, length), rects);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::HeadlessLoader;
+    use serde_yaml;
+
+    #[test]
+    fn finds_bodies_under_point() {
+        let data = LevelData::load("levels/level_1.lvl").unwrap();
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let level = Level::new(data, glm::uvec2(1280, 720), assets);
+
+        assert_eq!(level.body_at(glm::ivec2(130, 402)), Some(Body::METEOR));
+        assert_eq!(level.body_at(glm::ivec2(350, 300)), Some(Body::PLANET(0)));
+        assert_eq!(level.body_at(glm::ivec2(590, 90)), Some(Body::STAR(1)));
+        assert_eq!(level.body_at(glm::ivec2(859, 300)), Some(Body::ENEMY(1)));
+        assert_eq!(level.body_at(glm::ivec2(1200, 50)), None);
+    }
+
+    #[test]
+    fn field_pulls_toward_planets() {
+        let data = LevelData::load("levels/level_1.lvl").unwrap();
+        let planet = glm::dvec2(data.planets[0].x as f64, data.planets[0].y as f64);
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let level = Level::new(data, glm::uvec2(1280, 720), assets);
+
+        let point = planet + glm::dvec2(100., 0.);
+        let pull = level.field_at(point);
+        assert!(pull.x < 0. && pull.y.abs() < 1e-9);
+        assert_eq!(level.field_at(glm::dvec2(1200., 50.)), glm::dvec2(0., 0.));
+    }
+
+    #[test]
+    fn shots_take_turns_at_stations() {
+        let mut data = LevelData::load("levels/level_1.lvl").unwrap();
+        data.stations = vec![ObjectData { x: 100, y: 100 }];
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let mut level = Level::new(data, glm::uvec2(1280, 720), assets);
+
+        let target = glm::ivec2(130, 300);
+        for &start in &[glm::ivec2(100, 100), glm::ivec2(130, 402)] {
+            level.update(&PlayerInput::launch(target));
+            level.update(&PlayerInput::abort(target));
+            while !level.player.is_ready() {
+                level.update(&PlayerInput::aim(target));
+            }
+            assert_eq!(level.body_at(start), Some(Body::METEOR));
+        }
+    }
+
+    #[test]
+    fn only_piercing_meteors_fly_on() {
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        for &(meteors, left) in &[("[]", 1),
+                                  ("[{kind: PIERCING}]", 0),
+                                  ("[{kind: PIERCING, piercing: false}]", 1)] {
+            let yaml = format!("{{meteor: {{x: 100, y: 100}}, stars: [], planets: [], \
+                                enemies: [{{x: 400, y: 100}}, {{x: 700, y: 100}}], \
+                                meteors: {}}}",
+                               meteors);
+            let data: LevelData = serde_yaml::from_str(&yaml).unwrap();
+            let mut level = Level::new(data, glm::uvec2(1280, 720), assets.clone());
+
+            let target = glm::ivec2(200, 100);
+            level.update(&PlayerInput::aim(target));
+            level.update(&PlayerInput::launch(target));
+            while !level.player.is_ready() && level.outcome().is_none() {
+                level.update(&PlayerInput::aim(target));
+            }
+            assert_eq!(level.world.enemies.len(), left);
+        }
+    }
+
+    #[test]
+    fn picks_which_meteor_goes_next() {
+        let yaml = "{meteor: {x: 100, y: 100}, stars: [], planets: [], \
+                    enemies: [{x: 1000, y: 600}], meteors: [{}, {kind: HEAVY}, {kind: LIGHT}]}";
+        let data: LevelData = serde_yaml::from_str(yaml).unwrap();
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let mut level = Level::new(data, glm::uvec2(1280, 720), assets);
+        assert_eq!(level.max_shots, Some(3));
+
+        let target = glm::ivec2(200, 100);
+        let switch = PlayerInput { switch: true, ..PlayerInput::aim(target) };
+        level.update(&switch);
+        level.update(&switch);
+        assert_eq!(level.player.selected, 2);
+        level.update(&PlayerInput::launch(target));
+        assert_eq!(level.player.selected, 0);
+        let left = level.player.ammo.as_ref().unwrap().iter().map(|m| m.kind).collect::<Vec<_>>();
+        assert_eq!(left, vec![MeteorKind::ROCK, MeteorKind::HEAVY]);
+    }
+
+    #[test]
+    fn thrust_runs_out_of_fuel() {
+        let yaml = "{meteor: {x: 100, y: 100}, stars: [], planets: [], \
+                    enemies: [{x: 1000, y: 600}], fuel: 10}";
+        let data: LevelData = serde_yaml::from_str(yaml).unwrap();
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let mut level = Level::new(data, glm::uvec2(1280, 720), assets);
+
+        let target = glm::ivec2(200, 100);
+        let thrust = PlayerInput { thrust: glm::ivec2(0, 1), ..PlayerInput::aim(target) };
+        level.update(&PlayerInput::aim(target));
+        level.update(&PlayerInput::launch(target));
+        for _ in 0..20 {
+            level.update(&thrust);
+        }
+        assert_eq!(level.player.fuel, 0);
+        match level.player.state {
+            MeteorState::LAUNCHED(ref m) => {
+                // 0.1 more downwards speed for each of the first 10 ticks
+                assert!((m.center().y - (100. + 0.1 * (20. * 21. - 10. * 11.) / 2.)).abs() < 1e-9);
+            }
+            _ => panic!("meteor is not in flight"),
+        }
     }
 }
@@ -0,0 +1,14 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    CLEARED,
+    FAILED,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Score {
+    pub shots: u32,
+    pub stars: u32,
+    pub total_stars: u32,
+    /// Updates played until the level was over.
+    pub ticks: u32,
+}
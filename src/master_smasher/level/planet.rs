@@ -1,27 +1,40 @@
-use master_smasher::drawable::Rectifiable;
-use master_smasher::shape::{Circle, Intersect, Shape};
+use master_smasher::drawable::{Rectifiable, outline};
+use master_smasher::shape::{Circle, Collide, Contact, Intersect, Shape, Sweep, SweptCircle};
 use super::world_assets::WorldAssets;
 use super::collidable::Collidable;
-use super::level_data::{PlanetData, PlanetKind};
+use super::interpolate::State;
+use super::level_data::{Gravity, Motion, PlanetData, PlanetKind};
 
 use glm;
 use glm::ext::normalize_to;
 use moho::resource_manager::{Renderer, Scene, Texture, TextureId};
 use moho::errors as moho_errors;
 use num_traits::Zero;
+use sdl2::rect;
 
 use std::cmp;
+use std::f64::consts::PI;
 use std::time::Duration;
 
+#[derive(Clone, Copy)]
 struct Ring {
     body: Circle,
     strength: f64,
+    gravity: Gravity,
+    /// Pushes whichever way its law points.
+    repels: bool,
     zoom: f64,
     texture: TextureId,
 }
 
 impl Ring {
-    pub fn new(radius: f64, strength: f64, center: glm::IVec2, texture: TextureId) -> Self {
+    pub fn new(radius: f64,
+               strength: f64,
+               gravity: Gravity,
+               repels: bool,
+               center: glm::IVec2,
+               texture: TextureId)
+               -> Self {
         let body = Circle {
             radius: radius,
             center: glm::to_dvec2(center),
@@ -29,33 +42,58 @@ impl Ring {
         Ring {
             body: body,
             strength: strength,
+            gravity: gravity,
+            repels: repels,
             zoom: 1.,
             texture: texture,
         }
     }
 
+    /// Moves the ring as fast as its law pulls at its current radius, so a ring that pulls
+    /// hard near its edge moves fast there. Rings that pull shrink, rings that push grow.
     pub fn animate(&mut self, delta: Duration) {
         const K: f64 = 0.166;
+        // keeps laws that barely pull at the edge, like LINEAR, from stalling the ring there
+        const MIN_PULL: f64 = 1.;
         const NANO_IN_SEC: f64 = 1000000000.;
 
         let animated = self.animated_body();
-        let pull = self.pull(animated.radius);
+        let force = self.force(animated.radius);
+        let pull = force.abs().max(MIN_PULL);
         let time = delta.as_secs() as f64 + delta.subsec_nanos() as f64 / NANO_IN_SEC;
-        self.zoom *= 1. / 2_f64.powf(K * pull * time);
+        let halvings = K * pull * time;
+        if force < 0. {
+            self.zoom *= 2_f64.powf(halvings);
+        } else {
+            self.zoom *= 1. / 2_f64.powf(halvings);
+        }
     }
 
     pub fn pull_vector<B: Intersect<Circle> + Shape>(&self, body: &B) -> glm::DVec2 {
         if body.intersects(&self.body) {
             let dist = self.body.center - body.get_center();
-            let force = self.pull(glm::length(dist));
+            let force = self.force(glm::length(dist));
             normalize_to(dist, force)
         } else {
             glm::DVec2::zero()
         }
     }
 
+    /// Towards the planet; negative forces push away.
+    fn force(&self, distance: f64) -> f64 {
+        let pull = self.pull(distance);
+        if self.repels { -pull.abs() } else { pull }
+    }
+
+    /// What the law alone gives; negative pulls push away.
     fn pull(&self, distance: f64) -> f64 {
-        self.strength / (distance.powf(0.8))
+        match self.gravity {
+            Gravity::POWER { exponent } => self.strength / distance.powf(exponent),
+            Gravity::SQUARE => self.strength / (distance * distance),
+            Gravity::CONSTANT => self.strength,
+            Gravity::LINEAR => self.strength * (1. - distance / self.body.radius).max(0.),
+            Gravity::REPULSIVE { exponent } => -self.strength / distance.powf(exponent),
+        }
     }
 
     fn animated_body(&self) -> Circle {
@@ -66,16 +104,61 @@ impl Ring {
     }
 }
 
+/// Where a planet that started at `start` is after `ticks` of `motion`.
+fn position(motion: &Motion, start: glm::DVec2, ticks: u32) -> glm::DVec2 {
+    match *motion {
+        Motion::ORBIT { x, y, period } => {
+            let center = glm::dvec2(x as f64, y as f64);
+            let angle = 2. * PI * ticks as f64 / period;
+            let offset = start - center;
+            let (sin, cos) = angle.sin_cos();
+            center + glm::dvec2(offset.x * cos - offset.y * sin, offset.x * sin + offset.y * cos)
+        }
+        Motion::PATH { ref points, speed } => {
+            let mut waypoints = vec![start];
+            waypoints.extend(points.iter().map(|p| glm::dvec2(p.x as f64, p.y as f64)));
+            waypoints.push(start);
+
+            let length: f64 = waypoints.windows(2).map(|w| glm::distance(w[0], w[1])).sum();
+            if length <= 0. {
+                return start;
+            }
+            let mut left = (speed * ticks as f64) % length;
+            for w in waypoints.windows(2) {
+                let leg = glm::distance(w[0], w[1]);
+                if left < leg {
+                    return w[0] + (w[1] - w[0]) * (left / leg);
+                }
+                left -= leg;
+            }
+            start
+        }
+    }
+}
+
+/// What `planets` together add to the velocity of a `body` of `mass` each tick.
+pub fn acceleration<B>(planets: &[Planet], body: &B, mass: f64) -> glm::DVec2
+    where B: Intersect<Circle> + Shape
+{
+    planets.iter().map(|p| p.pull_vector(body)).fold(glm::DVec2::zero(), |c, a| c + a) / mass
+}
+
+#[derive(Clone)]
 pub struct Planet {
-    body: Circle,
+    body: State<Circle>,
     texture: TextureId,
     ring: Option<Ring>,
+    /// Restitution of the meteor bouncing off, if it does.
+    pub bouncy: Option<f64>,
+    motion: Option<Motion>,
+    start: glm::DVec2,
+    ticks: u32,
 }
 
 impl Planet {
     pub fn new(data: &PlanetData, textures: &WorldAssets) -> Self {
         let (texture, ring) = Self::load_assets(data, textures);
-        let radius = cmp::min(texture.dims.x, texture.dims.y) as f64 / 2.;
+        let radius = Self::texture_radius(texture);
         let center = glm::dvec2(data.x as f64, data.y as f64);
         let body = Circle {
             center: center,
@@ -83,16 +166,48 @@ impl Planet {
         };
 
         Planet {
-            body: body,
+            body: State::new(body),
             texture: texture.id,
             ring: ring,
+            bouncy: data.bouncy,
+            motion: data.motion.clone(),
+            start: center,
+            ticks: 0,
         }
     }
 
+    /// Moves the planet and its ring one tick along its orbit or path.
+    pub fn update(&mut self) {
+        let center = match self.motion {
+            Some(ref m) => position(m, self.start, self.ticks + 1),
+            None => return,
+        };
+        self.ticks += 1;
+        let body = Circle { center: center, ..self.body.current };
+        self.body.update(body);
+        if let Some(ref mut r) = self.ring {
+            r.body.center = center;
+        }
+    }
+
+    /// Radius of the planet's body, without loading the rest of it.
+    pub fn radius(data: &PlanetData, textures: &WorldAssets) -> f64 {
+        Self::texture_radius(Self::load_assets(data, textures).0)
+    }
+
+    fn texture_radius(texture: Texture) -> f64 {
+        cmp::min(texture.dims.x, texture.dims.y) as f64 / 2.
+    }
+
+    /// Shrinking rings start over at their full radius once they reach the planet, growing
+    /// ones start over at the planet once they reach their full radius.
     pub fn animate(&mut self, delta: Duration) {
         if let Some(ref mut r) = self.ring {
-            if r.body.radius * r.zoom < self.body.radius {
+            let inner = self.body.current.radius / r.body.radius;
+            if r.zoom < inner {
                 r.zoom = 1.;
+            } else if r.zoom > 1. {
+                r.zoom = inner;
             }
             r.animate(delta)
         }
@@ -102,6 +217,29 @@ impl Planet {
         self.ring.as_ref().map_or(glm::DVec2::zero(), |r| r.pull_vector(body))
     }
 
+    /// When `path` runs into the planet. A path starting out touching it, as a bounce leaves
+    /// it up to rounding, only does if it heads further in.
+    pub fn impact(&self, path: &SweptCircle) -> Option<f64> {
+        let body = &self.body.current;
+        let leaving = glm::dot(path.circle.center - body.center, path.displacement) >= 0.;
+        match path.time_of_impact(body) {
+            Some(t) if t == 0. && leaving => None,
+            impact => impact,
+        }
+    }
+
+    pub fn contact<S: Collide<Circle>>(&self, shape: &S) -> Option<Contact> {
+        shape.contact(&self.body.current)
+    }
+
+    /// The body and the full extent of the ring, regardless of how far it has shrunk.
+    pub fn outline(&self, rects: &mut Vec<rect::Rect>) {
+        outline::circle(&self.body.current, rects);
+        if let Some(ref r) = self.ring {
+            outline::circle(&r.body, rects);
+        }
+    }
+
     fn load_assets(data: &PlanetData, textures: &WorldAssets) -> (Texture, Option<Ring>) {
         let center = glm::ivec2(data.x, data.y);
         let (planet, ring) = match data.kind {
@@ -114,27 +252,36 @@ impl Planet {
             PlanetKind::WHITE { ring, strength } => {
                 (textures.white_planet, Some((textures.white_ring, ring, strength)))
             }
+            PlanetKind::PURPLE { ring, strength } => {
+                (textures.purple_planet, Some((textures.purple_ring, ring, strength)))
+            }
             PlanetKind::DEAD => (textures.dead_planet, None),
         };
 
-        let ring = ring.map(|(t, r, s)| Ring::new(r, s, center, t.id));
+        let repels = match data.kind {
+            PlanetKind::PURPLE { .. } => true,
+            _ => false,
+        };
+        let ring = ring.map(|(t, r, s)| Ring::new(r, s, data.gravity, repels, center, t.id));
 
         (planet, ring)
     }
-}
 
-impl<I: Intersect<Circle>> Collidable<Circle, I> for Planet {
-    fn collides(&self, shape: &I) -> bool {
-        shape.intersects(&self.body)
+    pub fn draw<R>(&self, interpolation: f64, renderer: &mut R) -> moho_errors::Result<()>
+        where R: Renderer
+    {
+        let body = self.body.interpolated(interpolation);
+        if let Some(r) = self.ring {
+            let ring = Ring { body: Circle { center: body.center, ..r.body }, ..r };
+            renderer.show(&ring)?;
+        }
+        renderer.render(&self.texture, body.rectify())
     }
 }
 
-impl Scene for Planet {
-    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
-        if let Some(ref r) = self.ring {
-            renderer.show(r)?;
-        }
-        renderer.render(&self.texture, self.body.rectify())
+impl<I: Intersect<Circle>> Collidable<Circle, I> for Planet {
+    fn collides(&self, shape: &I) -> bool {
+        shape.intersects(&self.body.current)
     }
 }
 
@@ -146,3 +293,77 @@ impl Scene for Ring {
         renderer.render(&self.texture, moving_rect)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::level::level_data::ObjectData;
+
+    fn ring(gravity: Gravity, repels: bool) -> Ring {
+        Ring::new(100., 400., gravity, repels, glm::ivec2(0, 0), TextureId(0))
+    }
+
+    fn pull(gravity: Gravity, distance: f64) -> f64 {
+        ring(gravity, false).pull(distance)
+    }
+
+    #[test]
+    fn gravity_laws() {
+        assert_eq!(pull(Gravity::POWER { exponent: 1. }, 50.), 8.);
+        assert_eq!(pull(Gravity::SQUARE, 20.), 1.);
+        assert_eq!(pull(Gravity::CONSTANT, 50.), 400.);
+        assert_eq!(pull(Gravity::LINEAR, 25.), 300.);
+        assert_eq!(pull(Gravity::LINEAR, 150.), 0.);
+        assert_eq!(pull(Gravity::REPULSIVE { exponent: 1. }, 50.), -8.);
+    }
+
+    #[test]
+    fn repulsive_rings_push_away() {
+        let body = Circle {
+            center: glm::dvec2(50., 0.),
+            radius: 10.,
+        };
+        assert!(ring(Gravity::default(), false).pull_vector(&body).x < 0.);
+        assert!(ring(Gravity::REPULSIVE { exponent: 1. }, false).pull_vector(&body).x > 0.);
+        assert!(ring(Gravity::default(), true).pull_vector(&body).x > 0.);
+        assert!(ring(Gravity::REPULSIVE { exponent: 1. }, true).pull_vector(&body).x > 0.);
+    }
+
+    #[test]
+    fn pushing_rings_grow() {
+        let mut pulling = ring(Gravity::default(), false);
+        let mut pushing = ring(Gravity::default(), true);
+        pulling.animate(Duration::from_millis(100));
+        pushing.animate(Duration::from_millis(100));
+        assert!(pulling.zoom < 1.);
+        assert!(pushing.zoom > 1.);
+    }
+
+    #[test]
+    fn orbits() {
+        let orbit = Motion::ORBIT {
+            x: 100,
+            y: 100,
+            period: 4.,
+        };
+        let start = glm::dvec2(150., 100.);
+        let quarter = position(&orbit, start, 1);
+        assert!(glm::distance(quarter, glm::dvec2(100., 150.)) < 1e-9);
+        assert!(glm::distance(position(&orbit, start, 4), start) < 1e-9);
+    }
+
+    #[test]
+    fn follows_paths_and_loops() {
+        let path = Motion::PATH {
+            points: vec![ObjectData { x: 10, y: 0 },
+                         ObjectData { x: 10, y: 10 },
+                         ObjectData { x: 0, y: 10 }],
+            speed: 4.,
+        };
+        let start = glm::dvec2(0., 0.);
+        assert_eq!(position(&path, start, 1), glm::dvec2(4., 0.));
+        assert_eq!(position(&path, start, 3), glm::dvec2(10., 2.));
+        assert_eq!(position(&path, start, 9), glm::dvec2(0., 4.));
+        assert_eq!(position(&path, start, 10), start);
+    }
+}
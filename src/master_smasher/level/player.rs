@@ -1,15 +1,18 @@
 use master_smasher::drawable::Animation;
 use super::unlaunched_meteor::UnlaunchedMeteor;
+use super::bounds::Bounds;
 use super::launched_meteor::LaunchedMeteor;
+use super::level_data::{MeteorType, Physics};
 use super::planet::Planet;
+use super::portal::Portal;
 use super::player_assets::PlayerAssets;
+use super::player_input::PlayerInput;
+use master_smasher::GAME_SPEED;
 use errors::*;
 
 use glm;
-use moho::input_manager::{EventPump, InputManager};
 use moho::resource_manager::Renderer;
-use sdl2::keyboard::Keycode;
-use sdl2::mouse::MouseButton;
+use sdl2::rect;
 
 use std::time::Duration;
 
@@ -21,51 +24,95 @@ pub enum MeteorState {
 
 pub struct Player {
     pub state: MeteorState,
-    max_coords: glm::UVec2,
+    pub shots: u32,
+    /// Ticks of thrust left.
+    pub fuel: u32,
+    bounds: Bounds,
+    physics: Physics,
     assets: PlayerAssets,
-    initial_center: glm::IVec2,
+    /// Where each shot starts from in turn.
+    starts: Vec<glm::IVec2>,
+    /// Meteors left to launch, if the level hands out particular ones.
+    pub ammo: Option<Vec<MeteorType>>,
+    /// Which of the ammo goes next.
+    pub selected: usize,
+    preview_steps: u32,
 }
 
 impl Player {
-    pub fn new(assets: PlayerAssets, center: glm::IVec2, max_coords: glm::UVec2) -> Self {
-        let meteor = UnlaunchedMeteor::load(&assets, center);
+    pub fn new(assets: PlayerAssets,
+               starts: Vec<glm::IVec2>,
+               ammo: Option<Vec<MeteorType>>,
+               bounds: Bounds,
+               physics: Physics,
+               fuel: u32,
+               preview_steps: u32)
+               -> Self {
+        let first = ammo.as_ref().and_then(|a| a.first()).cloned().unwrap_or_default();
+        let meteor = UnlaunchedMeteor::load(&assets, starts[0], first, preview_steps);
         let state = MeteorState::UNLAUNCHED(meteor);
         Player {
             state: state,
-            max_coords: max_coords,
+            shots: 0,
+            fuel: fuel,
+            bounds: bounds,
+            physics: physics,
             assets: assets,
-            initial_center: center,
+            starts: starts,
+            ammo: ammo,
+            selected: 0,
+            preview_steps: preview_steps,
         }
     }
 
-    pub fn update<E: EventPump>(&mut self, planets: &[Planet], input_manager: &InputManager<E>) {
-        let target = input_manager.mouse_coords();
+    /// Returns whether the meteor in flight crashed. It is left at the point of impact so what
+    /// it hit on the way can still be collided with, and is up to the caller to `crash`.
+    pub fn update(&mut self, planets: &[Planet], portals: &[Portal], input: &PlayerInput) -> bool {
+        let target = input.target;
+        let mut crashed = false;
 
         let next_state = match self.state {
-            MeteorState::UNLAUNCHED(ref m) if input_manager.did_click_mouse(MouseButton::Left) => {
-                Some(m.launch(self.max_coords))
+            MeteorState::UNLAUNCHED(ref m) if input.launch => {
+                self.shots += 1;
+                if let Some(ref mut ammo) = self.ammo {
+                    if self.selected < ammo.len() {
+                        ammo.remove(self.selected);
+                    }
+                    self.selected = 0;
+                }
+                Some(m.launch(self.bounds, self.physics))
+            }
+            MeteorState::UNLAUNCHED(_) if input.switch && self.choices() > 1 => {
+                self.selected = (self.selected + 1) % self.choices();
+                let mut meteor = self.load();
+                meteor.update(target, planets, portals, self.bounds, self.physics);
+                Some(MeteorState::UNLAUNCHED(meteor))
             }
-            MeteorState::LAUNCHED(ref m) if input_manager.did_press_key(Keycode::R) => {
+            MeteorState::LAUNCHED(ref m) if input.abort => {
                 Some(m.explode(self.assets.explosion.clone()))
             }
             MeteorState::UNLAUNCHED(ref mut m) => {
-                m.update(target);
+                m.update(target, planets, portals, self.bounds, self.physics);
                 None
             }
             MeteorState::LAUNCHED(ref mut m) => {
-                m.update(planets);
-                if planets.iter().any(|p| m.collides(p)) {
-                    Some(m.explode(self.assets.explosion.clone()))
-                } else {
-                    None
+                if self.fuel > 0 {
+                    if let Some(direction) = input.thrust_direction(m.center()) {
+                        m.push(direction * self.physics.thrust);
+                        self.fuel -= 1;
+                    }
                 }
+                crashed = m.update(planets, portals);
+                None
             }
             MeteorState::EXPLODED(ref mut a) => {
+                // advanced here rather than in animate so respawning only depends on ticks
+                a.update(Duration::new(0, 1000000000 / GAME_SPEED));
                 if a.is_active() {
                     None
                 } else {
-                    let mut meteor = UnlaunchedMeteor::load(&self.assets, self.initial_center);
-                    meteor.update(target);
+                    let mut meteor = self.load();
+                    meteor.update(target, planets, portals, self.bounds, self.physics);
                     Some(MeteorState::UNLAUNCHED(meteor))
                 }
             }
@@ -74,20 +121,63 @@ impl Player {
         if let Some(s) = next_state {
             self.state = s;
         }
+        crashed
     }
 
-    pub fn animate(&mut self, delta: Duration) {
-        if let MeteorState::EXPLODED(ref mut a) = self.state {
-            a.update(delta);
+    fn choices(&self) -> usize {
+        self.ammo.as_ref().map_or(1, |a| a.len())
+    }
+
+    /// The selected meteor at the start of the next shot; a plain one once the ammo is gone.
+    fn load(&self) -> UnlaunchedMeteor {
+        let start = self.starts[self.shots as usize % self.starts.len()];
+        let meteor = self.ammo
+            .as_ref()
+            .and_then(|a| a.get(self.selected))
+            .cloned()
+            .unwrap_or_default();
+        UnlaunchedMeteor::load(&self.assets, start, meteor, self.preview_steps)
+    }
+
+    /// Blows up the meteor in flight, if there is one.
+    pub fn crash(&mut self) {
+        let exploded = match self.state {
+            MeteorState::LAUNCHED(ref m) => m.explode(self.assets.explosion.clone()),
+            _ => return,
+        };
+        self.state = exploded;
+    }
+
+    pub fn is_ready(&self) -> bool {
+        match self.state {
+            MeteorState::UNLAUNCHED(_) => true,
+            _ => false,
         }
     }
 
+    pub fn outline(&self, planets: &[Planet], rects: &mut Vec<rect::Rect>) {
+        match self.state {
+            MeteorState::UNLAUNCHED(ref m) => m.outline(rects),
+            MeteorState::LAUNCHED(ref m) => m.outline(planets, rects),
+            MeteorState::EXPLODED(_) => {}
+        }
+    }
+
+    /// Launch stations are only drawn under the starts when there is more than one.
     pub fn draw<R>(&self, interpolation: f64, renderer: &mut R) -> Result<()>
         where R: Renderer
     {
+        if self.starts.len() > 1 {
+            let dims = glm::to_ivec2(self.assets.station.dims);
+            for start in &self.starts {
+                let corner = *start - dims / 2;
+                let rect = glm::ivec4(corner.x, corner.y, dims.x, dims.y);
+                renderer.render(&self.assets.station.id, rect)?;
+            }
+        }
         match self.state {
             MeteorState::LAUNCHED(ref m) => m.draw(interpolation, renderer),
-            MeteorState::UNLAUNCHED(ref m) => m.draw(interpolation, renderer),
+            MeteorState::UNLAUNCHED(ref m) => m.draw(renderer),
             MeteorState::EXPLODED(ref a) => renderer.show(a).map_err(Into::into),
         }
     }
@@ -1,4 +1,5 @@
 use master_smasher::drawable::AnimationData;
+use super::level_data::MeteorKind;
 
 use moho::errors::*;
 use moho::resource_manager::{ResourceLoader, Texture};
@@ -6,18 +7,43 @@ use moho::resource_manager::{ResourceLoader, Texture};
 #[derive(Clone)]
 pub struct PlayerAssets {
     pub meteor: Texture,
+    pub heavy_meteor: Texture,
+    pub light_meteor: Texture,
+    pub piercing_meteor: Texture,
+    pub bouncy_meteor: Texture,
+    pub station: Texture,
     pub explosion: AnimationData,
 }
 
 impl PlayerAssets {
     pub fn new<R: ResourceLoader>(resource_manager: &R) -> Result<Self> {
         let meteor = resource_manager.load_texture("resources/meteor.png")?;
+        let heavy_meteor = resource_manager.load_texture("resources/heavy_meteor.png")?;
+        let light_meteor = resource_manager.load_texture("resources/light_meteor.png")?;
+        let piercing_meteor = resource_manager.load_texture("resources/piercing_meteor.png")?;
+        let bouncy_meteor = resource_manager.load_texture("resources/bouncy_meteor.png")?;
+        let station = resource_manager.load_texture("resources/StationLauncher.png")?;
         let explosion_path = "resources/explosion_large.png";
         let explosion = AnimationData::new(explosion_path, 8, 80, false, resource_manager)?;
         let assets = PlayerAssets {
             meteor: meteor,
+            heavy_meteor: heavy_meteor,
+            light_meteor: light_meteor,
+            piercing_meteor: piercing_meteor,
+            bouncy_meteor: bouncy_meteor,
+            station: station,
             explosion: explosion,
         };
         Ok(assets)
     }
+
+    pub fn meteor(&self, kind: MeteorKind) -> Texture {
+        match kind {
+            MeteorKind::ROCK => self.meteor,
+            MeteorKind::HEAVY => self.heavy_meteor,
+            MeteorKind::LIGHT => self.light_meteor,
+            MeteorKind::PIERCING => self.piercing_meteor,
+            MeteorKind::BOUNCY => self.bouncy_meteor,
+        }
+    }
 }
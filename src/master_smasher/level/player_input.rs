@@ -0,0 +1,71 @@
+use glm;
+use glm::ext::normalize_to;
+use moho::input_manager::{EventPump, InputManager};
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayerInput {
+    pub target: glm::IVec2,
+    pub launch: bool,
+    pub abort: bool,
+    pub restart: bool,
+    /// Direction the keys thrust the meteor in, -1, 0 or 1 along each axis.
+    pub thrust: glm::IVec2,
+    /// Thrust towards `target` instead.
+    pub steer: bool,
+    /// Picks the next of the meteors left to launch.
+    pub switch: bool,
+}
+
+impl PlayerInput {
+    pub fn aim(target: glm::IVec2) -> Self {
+        PlayerInput {
+            target: target,
+            launch: false,
+            abort: false,
+            restart: false,
+            thrust: glm::ivec2(0, 0),
+            steer: false,
+            switch: false,
+        }
+    }
+
+    pub fn launch(target: glm::IVec2) -> Self {
+        PlayerInput { launch: true, ..Self::aim(target) }
+    }
+
+    pub fn abort(target: glm::IVec2) -> Self {
+        PlayerInput { abort: true, ..Self::aim(target) }
+    }
+
+    /// Unit vector the meteor at `center` is thrust in, if any.
+    pub fn thrust_direction(&self, center: glm::DVec2) -> Option<glm::DVec2> {
+        let direction = if self.steer {
+            glm::to_dvec2(self.target) - center
+        } else {
+            glm::to_dvec2(self.thrust)
+        };
+        if direction == glm::dvec2(0., 0.) {
+            None
+        } else {
+            Some(normalize_to(direction, 1.))
+        }
+    }
+
+    pub fn from_input<E: EventPump>(input_manager: &InputManager<E>) -> Self {
+        let held = |keys: [Keycode; 2]| keys.iter().any(|&k| input_manager.is_key_down(k)) as i32;
+        let axis = |plus, minus| held(plus) - held(minus);
+        let thrust = glm::ivec2(axis([Keycode::D, Keycode::Right], [Keycode::A, Keycode::Left]),
+                                axis([Keycode::S, Keycode::Down], [Keycode::W, Keycode::Up]));
+        PlayerInput {
+            target: input_manager.mouse_coords(),
+            launch: input_manager.did_click_mouse(MouseButton::Left),
+            abort: input_manager.did_press_key(Keycode::R),
+            restart: input_manager.did_press_key(Keycode::Backspace),
+            thrust: thrust,
+            steer: input_manager.is_mouse_down(MouseButton::Right),
+            switch: input_manager.did_press_key(Keycode::Tab),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use master_smasher::drawable::{Rectifiable, outline};
+use master_smasher::shape::{Circle, Sweep, SweptCircle};
+use super::bounds::Fold;
+use super::level_data::{ObjectData, PortalData};
+
+use glm;
+use moho::resource_manager::{Renderer, Scene, Texture, TextureId};
+use moho::errors as moho_errors;
+use sdl2::rect;
+
+use std::cmp;
+
+pub struct Portal {
+    ends: [Circle; 2],
+    /// Radians the velocity is turned going from the first end to the second.
+    rotation: f64,
+    texture: TextureId,
+}
+
+impl Portal {
+    pub fn new(data: &PortalData, texture: Texture) -> Self {
+        let radius = cmp::min(texture.dims.x, texture.dims.y) as f64 / 2.;
+        let end = |o: &ObjectData| {
+            Circle {
+                center: glm::dvec2(o.x as f64, o.y as f64),
+                radius: radius,
+            }
+        };
+
+        Portal {
+            ends: [end(&data.a), end(&data.b)],
+            rotation: data.rotation.to_radians(),
+            texture: texture.id,
+        }
+    }
+
+    /// Earliest fraction of `path` at which its center goes into `end`.
+    pub fn entry(&self, end: usize, path: &SweptCircle) -> Option<f64> {
+        let center = SweptCircle {
+            circle: Circle { radius: 0., ..path.circle },
+            displacement: path.displacement,
+        };
+        center.time_of_impact(&self.ends[end])
+    }
+
+    /// Takes whatever went into `end` out of the other one.
+    pub fn fold(&self, end: usize) -> Fold {
+        let rotation = if end == 0 { self.rotation } else { -self.rotation };
+        Fold::turn(rotation, self.ends[end].center, self.ends[1 - end].center)
+    }
+
+    pub fn covers(&self, end: usize, point: glm::DVec2) -> bool {
+        glm::distance(self.ends[end].center, point) < self.ends[end].radius
+    }
+
+    /// Both ends and what links them.
+    pub fn outline(&self, rects: &mut Vec<rect::Rect>) {
+        outline::circle(&self.ends[0], rects);
+        outline::circle(&self.ends[1], rects);
+        outline::segment(self.ends[0].center, self.ends[1].center, rects);
+    }
+}
+
+impl Scene for Portal {
+    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
+        renderer.render(&self.texture, self.ends[0].rectify())?;
+        renderer.render(&self.texture, self.ends[1].rectify())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use moho::resource_manager::TextureId;
+
+    fn portal(rotation: f64) -> Portal {
+        let data = PortalData {
+            a: ObjectData { x: 100, y: 100 },
+            b: ObjectData { x: 500, y: 300 },
+            rotation: rotation,
+        };
+        let texture = Texture {
+            id: TextureId(0),
+            dims: glm::uvec2(64, 64),
+        };
+        Portal::new(&data, texture)
+    }
+
+    #[test]
+    fn entered_by_the_center() {
+        let portal = portal(0.);
+        let grazing = SweptCircle {
+            circle: Circle {
+                center: glm::dvec2(0., 140.),
+                radius: 30.,
+            },
+            displacement: glm::dvec2(200., 0.),
+        };
+        assert_eq!(portal.entry(0, &grazing), None);
+
+        let through = SweptCircle {
+            circle: Circle { center: glm::dvec2(0., 100.), ..grazing.circle },
+            ..grazing
+        };
+        assert_eq!(portal.entry(0, &through), Some(68. / 200.));
+        assert_eq!(portal.entry(1, &through), None);
+    }
+
+    #[test]
+    fn turns_back_the_other_way() {
+        let portal = portal(90.);
+        let there = portal.fold(0).deflect(glm::dvec2(1., 0.));
+        assert!(glm::distance(there, glm::dvec2(0., 1.)) < 1e-9);
+        let back = portal.fold(1).deflect(there);
+        assert!(glm::distance(back, glm::dvec2(1., 0.)) < 1e-9);
+        assert!(glm::distance(portal.fold(0).apply(glm::dvec2(110., 100.)),
+                              glm::dvec2(500., 310.)) < 1e-9);
+    }
+}
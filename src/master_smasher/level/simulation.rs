@@ -0,0 +1,226 @@
+use super::{Level, LevelAssets, LevelData, Outcome, PlayerInput, Score};
+use master_smasher::GAME_SPEED;
+
+use glm;
+
+use std::time::Duration;
+
+pub struct Simulation {
+    level: Level,
+}
+
+impl Simulation {
+    pub fn new(data: LevelData, window_size: glm::UVec2, assets: LevelAssets) -> Self {
+        Simulation { level: Level::new(data, window_size, assets) }
+    }
+
+    pub fn step(&mut self, input: &PlayerInput) {
+        self.level.update(input);
+        self.level.animate(Duration::new(0, 1000000000 / GAME_SPEED));
+    }
+
+    /// Launches the meteor towards `target` and steps until it can be launched again or the
+    /// level is over. A meteor still flying after `max_ticks` is aborted.
+    pub fn shoot(&mut self, target: glm::IVec2, max_ticks: u32) {
+        while !self.is_ready() && self.outcome().is_none() {
+            self.step(&PlayerInput::aim(target));
+        }
+        if self.outcome().is_some() {
+            return;
+        }
+
+        self.step(&PlayerInput::aim(target));
+        self.step(&PlayerInput::launch(target));
+
+        let mut flying = 0;
+        while !self.is_ready() && self.outcome().is_none() {
+            let input = if flying < max_ticks {
+                PlayerInput::aim(target)
+            } else {
+                PlayerInput::abort(target)
+            };
+            self.step(&input);
+            flying += 1;
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.level.player.is_ready()
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.level.outcome()
+    }
+
+    pub fn score(&self) -> Score {
+        self.level.score()
+    }
+
+    pub fn enemies_left(&self) -> usize {
+        self.level.world.enemies.len()
+    }
+
+    pub fn stars_left(&self) -> usize {
+        self.level.world.stars.len()
+    }
+
+    pub fn enemy_centers(&self) -> Vec<glm::DVec2> {
+        self.level.world.enemies.iter().map(|e| e.center()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::level_data::{MeteorKind, MeteorType};
+    use super::super::player::MeteorState;
+    use master_smasher::HeadlessLoader;
+
+    use serde_yaml;
+
+    fn level_1() -> Simulation {
+        let data = LevelData::load("levels/level_1.lvl").unwrap();
+        simulation(data)
+    }
+
+    fn simulation(data: LevelData) -> Simulation {
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        Simulation::new(data, glm::uvec2(1280, 720), assets)
+    }
+
+    #[test]
+    fn starts_ready_to_launch() {
+        let simulation = level_1();
+        assert!(simulation.is_ready());
+        assert_eq!(simulation.enemies_left(), 2);
+        assert_eq!(simulation.stars_left(), 3);
+    }
+
+    #[test]
+    fn launch_leaves_meteor_flying() {
+        let mut simulation = level_1();
+        let target = glm::ivec2(130, 100);
+        simulation.step(&PlayerInput::aim(target));
+        simulation.step(&PlayerInput::launch(target));
+        assert!(!simulation.is_ready());
+    }
+
+    #[test]
+    fn abort_respawns_meteor() {
+        let mut simulation = level_1();
+        simulation.shoot(glm::ivec2(130, 100), 0);
+        assert!(simulation.is_ready());
+        assert_eq!(simulation.enemies_left(), 2);
+    }
+
+    #[test]
+    fn piercing_shot_clears_level() {
+        let mut data = LevelData::load("levels/level_1.lvl").unwrap();
+        data.meteors = vec![MeteorType { kind: MeteorKind::PIERCING, ..MeteorType::default() }];
+        let mut simulation = simulation(data);
+        simulation.shoot(glm::ivec2(150, 120), 60 * 30);
+        assert_eq!(simulation.enemies_left(), 0);
+        assert_eq!(simulation.stars_left(), 0);
+        assert_eq!(simulation.outcome(), Some(Outcome::CLEARED));
+        assert_eq!(simulation.score(),
+                   Score {
+                       shots: 1,
+                       stars: 3,
+                       total_stars: 3,
+                       ticks: 321,
+                   });
+    }
+
+    #[test]
+    fn restart_restores_level() {
+        let mut simulation = level_1();
+        simulation.shoot(glm::ivec2(150, 120), 60 * 30);
+        simulation.step(&PlayerInput { restart: true, ..PlayerInput::aim(glm::ivec2(0, 0)) });
+        assert_eq!(simulation.enemies_left(), 2);
+        assert_eq!(simulation.stars_left(), 3);
+        assert_eq!(simulation.outcome(), None);
+        assert_eq!(simulation.score().shots, 0);
+        assert!(simulation.is_ready());
+    }
+
+    #[test]
+    fn fails_when_out_of_shots() {
+        let mut data = LevelData::load("levels/level_1.lvl").unwrap();
+        data.shots = Some(2);
+        let mut simulation = simulation(data);
+
+        simulation.shoot(glm::ivec2(130, 100), 0);
+        assert_eq!(simulation.outcome(), None);
+        simulation.shoot(glm::ivec2(130, 100), 0);
+        assert_eq!(simulation.outcome(), Some(Outcome::FAILED));
+    }
+
+    #[test]
+    fn unlimited_shots_never_fail() {
+        let mut simulation = level_1();
+        for _ in 0..5 {
+            simulation.shoot(glm::ivec2(130, 100), 0);
+        }
+        assert_eq!(simulation.outcome(), None);
+        assert_eq!(simulation.score().shots, 5);
+    }
+
+    #[test]
+    fn crashing_meteor_destroys_what_it_passed() {
+        let yaml = "{meteor: {x: 100, y: 100}, stars: [], \
+                    planets: [{x: 600, y: 100, kind: DEAD}], \
+                    enemies: [{x: 300, y: 100}, {x: 1000, y: 600}]}";
+        let data: LevelData = serde_yaml::from_str(yaml).unwrap();
+        let mut simulation = simulation(data);
+
+        // 450 pixels a tick, past the enemy and into the planet in one go
+        let target = glm::ivec2(100 + 450 * 50, 100);
+        simulation.step(&PlayerInput::aim(target));
+        simulation.step(&PlayerInput::launch(target));
+        simulation.step(&PlayerInput::aim(target));
+        assert_eq!(simulation.enemies_left(), 1);
+        match simulation.level.player.state {
+            MeteorState::EXPLODED(_) => {}
+            _ => panic!("meteor did not crash"),
+        }
+    }
+
+    #[test]
+    fn crashing_across_wrap_destroys_only_what_it_passed() {
+        let yaml = "{meteor: {x: 1200, y: 100}, stars: [], \
+                    planets: [{x: 150, y: 100, kind: DEAD}], \
+                    enemies: [{x: 640, y: 100}, {x: 1000, y: 600}]}";
+        let data: LevelData = serde_yaml::from_str(yaml).unwrap();
+        let mut simulation = simulation(data);
+
+        // 200 pixels a tick, over the right edge and into the planet on the left
+        let target = glm::ivec2(1200 + 200 * 50, 100);
+        simulation.step(&PlayerInput::aim(target));
+        simulation.step(&PlayerInput::launch(target));
+        simulation.step(&PlayerInput::aim(target));
+        assert_eq!(simulation.enemies_left(), 2);
+        match simulation.level.player.state {
+            MeteorState::EXPLODED(_) => {}
+            _ => panic!("meteor did not crash"),
+        }
+    }
+
+    #[test]
+    fn bouncing_across_wrap_destroys_only_what_it_passed() {
+        let yaml = "{meteor: {x: 1200, y: 100}, stars: [], \
+                    planets: [{x: 150, y: 100, kind: DEAD, bouncy: 1}], \
+                    enemies: [{x: 640, y: 100}, {x: 1000, y: 600}]}";
+        let data: LevelData = serde_yaml::from_str(yaml).unwrap();
+        let mut simulation = simulation(data);
+
+        let target = glm::ivec2(1200 + 200 * 50, 100);
+        simulation.step(&PlayerInput::aim(target));
+        simulation.step(&PlayerInput::launch(target));
+        simulation.step(&PlayerInput::aim(target));
+        assert_eq!(simulation.enemies_left(), 2);
+        match simulation.level.player.state {
+            MeteorState::LAUNCHED(_) => {}
+            _ => panic!("meteor did not bounce"),
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use super::collidable::Collidable;
-use master_smasher::drawable::Animation;
+use master_smasher::drawable::{Animation, outline};
 use master_smasher::shape::{Intersect, Rectangle};
 
 use glm;
 use moho::resource_manager::{Renderer, Scene};
 use moho::errors as moho_errors;
+use sdl2::rect;
 
 use std::time::Duration;
 
@@ -37,6 +38,10 @@ impl Star {
     pub fn center(&self) -> glm::DVec2 {
         self.body.center
     }
+
+    pub fn outline(&self, rects: &mut Vec<rect::Rect>) {
+        outline::rectangle(&self.body, rects);
+    }
 }
 
 impl<I: Intersect<Rectangle>> Collidable<Rectangle, I> for Star {
@@ -1,13 +1,16 @@
-use master_smasher::drawable::Rectifiable;
-use master_smasher::shape::Circle;
+use master_smasher::drawable::{Rectifiable, outline};
+use master_smasher::shape::{Circle, Intersect};
+use super::collidable::Collidable;
 use super::player_assets::PlayerAssets;
 use super::MeteorState;
-use super::interpolate::State;
+use super::bounds::Bounds;
 use super::launched_meteor::LaunchedMeteor;
+use super::level_data::{MeteorType, Physics};
+use super::planet::Planet;
+use super::portal::Portal;
 use errors::*;
 
 use glm;
-use glm::ext::normalize_to;
 use moho::resource_manager::{Renderer, TextureId};
 use sdl2::rect;
 
@@ -16,12 +19,19 @@ use std::cmp;
 pub struct UnlaunchedMeteor {
     body: Circle,
     texture: TextureId,
-    target: State<glm::IVec2>,
+    meteor: MeteorType,
+    target: glm::IVec2,
+    preview_steps: u32,
+    preview: Vec<glm::DVec2>,
 }
 
 impl UnlaunchedMeteor {
-    pub fn load(assets: &PlayerAssets, center: glm::IVec2) -> Self {
-        let texture = assets.meteor;
+    pub fn load(assets: &PlayerAssets,
+                center: glm::IVec2,
+                meteor: MeteorType,
+                preview_steps: u32)
+                -> Self {
+        let texture = assets.meteor(meteor.kind);
         let center = glm::to_dvec2(center);
         let radius = cmp::min(texture.dims.x, texture.dims.y) as f64 / 2.;
         let body = Circle {
@@ -29,49 +39,185 @@ impl UnlaunchedMeteor {
             radius: radius,
         };
 
-        Self::new(body, texture.id)
+        Self::new(body, texture.id, meteor, preview_steps)
     }
 
-    pub fn new(body: Circle, texture: TextureId) -> Self {
+    pub fn new(body: Circle, texture: TextureId, meteor: MeteorType, preview_steps: u32) -> Self {
         UnlaunchedMeteor {
             body: body,
             texture: texture,
-            target: State::new(glm::ivec2(0, 0)),
+            meteor: meteor,
+            target: glm::ivec2(0, 0),
+            preview_steps: preview_steps,
+            preview: vec![],
         }
     }
 
-    pub fn update(&mut self, target: glm::IVec2) {
-        self.target.update(target);
+    pub fn update(&mut self,
+                  target: glm::IVec2,
+                  planets: &[Planet],
+                  portals: &[Portal],
+                  bounds: Bounds,
+                  physics: Physics) {
+        self.target = target;
+        self.preview = self.predict(planets, portals, bounds, physics);
     }
 
-    pub fn draw<R: Renderer>(&self, interpolation: f64, renderer: &mut R) -> Result<()> {
-        let target = glm::to_dvec2(self.target.interpolated(interpolation));
-        let rects = self.target_rects(target, self.body.center);
-        renderer.render(&self.texture, self.body.rectify())?;
-        renderer.fill_rects(&rects).map_err(Into::into)
+    pub fn outline(&self, rects: &mut Vec<rect::Rect>) {
+        outline::circle(&self.body, rects);
     }
 
-    fn target_rects(&self, target: glm::DVec2, center: glm::DVec2) -> Vec<rect::Rect> {
-        const NUM_RECTS: u32 = 10;
+    pub fn draw<R: Renderer>(&self, renderer: &mut R) -> Result<()> {
         const SIDE_LEN: u32 = 5;
 
-        let distance = target - center;
-        let offset = self.body.radius + 10.;
-        let offset_vector = normalize_to(distance, offset);
-        let anchor_point = center + offset_vector;
-        let step = (target - anchor_point) / NUM_RECTS as f64;
-
-        (0..NUM_RECTS)
-            .map(|i| anchor_point + (step * i as f64))
+        let rects: Vec<_> = self.preview
+            .iter()
             .map(|p| (p.x as i32, p.y as i32))
             .map(|p| rect::Rect::from_center(p, SIDE_LEN, SIDE_LEN))
-            .collect()
+            .collect();
+        renderer.render(&self.texture, self.body.rectify())?;
+        renderer.fill_rects(&rects).map_err(Into::into)
     }
 
-    pub fn launch(&self, max_coords: glm::UVec2) -> MeteorState {
-        const FACTOR: f64 = 50.;
-        let offset = self.target.current - glm::to_ivec2(self.body.center);
-        let velocity = glm::to_dvec2(offset) / FACTOR;
-        MeteorState::LAUNCHED(LaunchedMeteor::new(self.body, self.texture, max_coords, velocity))
+    pub fn launch(&self, bounds: Bounds, physics: Physics) -> MeteorState {
+        MeteorState::LAUNCHED(self.launched(bounds, physics))
+    }
+
+    fn launched(&self, bounds: Bounds, physics: Physics) -> LaunchedMeteor {
+        let offset = self.target - glm::to_ivec2(self.body.center);
+        let meteor = &self.meteor;
+        let velocity = glm::to_dvec2(offset) / physics.launch * meteor.speed();
+        LaunchedMeteor::new(self.body,
+                            self.texture,
+                            bounds,
+                            velocity,
+                            physics.mass * meteor.mass(),
+                            meteor.piercing(),
+                            meteor.bouncy())
+    }
+
+    /// Runs a launch ahead of time and returns every few of its positions, up until it would
+    /// crash into a planet. Moving planets are moved along with it, as if it was launched on
+    /// the next tick.
+    fn predict(&self,
+               planets: &[Planet],
+               portals: &[Portal],
+               bounds: Bounds,
+               physics: Physics)
+               -> Vec<glm::DVec2> {
+        const DOT_SPACING: u32 = 3;
+
+        let mut planets = planets.to_vec();
+        let mut meteor = self.launched(bounds, physics);
+        let mut dots = vec![];
+        for p in &mut planets {
+            p.update();
+        }
+        for step in 1..self.preview_steps + 1 {
+            for p in &mut planets {
+                p.update();
+            }
+            if meteor.update(&planets, portals) {
+                break;
+            }
+            if step % DOT_SPACING == 0 {
+                dots.push(meteor.center());
+            }
+        }
+        dots
+    }
+}
+
+impl<I: Intersect<Circle>> Collidable<Circle, I> for UnlaunchedMeteor {
+    fn collides(&self, shape: &I) -> bool {
+        shape.intersects(&self.body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::HeadlessLoader;
+    use master_smasher::level::level_data::{Edges, Gravity, PlanetData, PlanetKind};
+    use master_smasher::level::world_assets::WorldAssets;
+
+    fn bounds() -> Bounds {
+        Bounds::new(glm::uvec2(1280, 720), Edges::default())
+    }
+
+    fn meteor(preview_steps: u32) -> UnlaunchedMeteor {
+        typed(MeteorType::default(), preview_steps)
+    }
+
+    fn typed(meteor: MeteorType, preview_steps: u32) -> UnlaunchedMeteor {
+        let assets = PlayerAssets::new(&HeadlessLoader::default()).unwrap();
+        UnlaunchedMeteor::load(&assets, glm::ivec2(100, 100), meteor, preview_steps)
+    }
+
+    #[test]
+    fn straight_preview_without_planets() {
+        let mut meteor = meteor(9);
+        meteor.update(glm::ivec2(200, 100), &[], &[], bounds(), Physics::default());
+        assert_eq!(meteor.preview,
+                   vec![glm::dvec2(106., 100.), glm::dvec2(112., 100.), glm::dvec2(118., 100.)]);
+    }
+
+    fn blue_planet() -> Planet {
+        let assets = WorldAssets::new(&HeadlessLoader::default()).unwrap();
+        let data = PlanetData {
+            x: 300,
+            y: 300,
+            kind: PlanetKind::BLUE {
+                ring: 300.,
+                strength: 700.,
+            },
+            gravity: Gravity::default(),
+            bouncy: None,
+            motion: None,
+        };
+        Planet::new(&data, &assets)
+    }
+
+    #[test]
+    fn preview_bends_towards_planets() {
+        let planets = [blue_planet()];
+        let mut meteor = meteor(30);
+        meteor.update(glm::ivec2(200, 100), &planets, &[], bounds(), Physics::default());
+        assert!(meteor.preview.iter().all(|p| p.y > 100.));
+    }
+
+    #[test]
+    fn preview_stops_at_planets() {
+        let assets = WorldAssets::new(&HeadlessLoader::default()).unwrap();
+        let data = PlanetData {
+            x: 200,
+            y: 100,
+            kind: PlanetKind::DEAD,
+            gravity: Gravity::default(),
+            bouncy: None,
+            motion: None,
+        };
+        let planets = [Planet::new(&data, &assets)];
+
+        let mut meteor = meteor(600);
+        meteor.update(glm::ivec2(200, 100), &planets, &[], bounds(), Physics::default());
+        assert_eq!(meteor.preview.len(), 5);
+    }
+
+    #[test]
+    fn meteor_types_fly_differently() {
+        let planets = [blue_planet()];
+        let light = MeteorType { mass: Some(0.5), ..MeteorType::default() };
+        let slow = MeteorType { speed: Some(0.5), ..MeteorType::default() };
+        let mut rock = meteor(30);
+        let mut light = typed(light, 30);
+        let mut slow = typed(slow, 9);
+        rock.update(glm::ivec2(200, 100), &planets, &[], bounds(), Physics::default());
+        light.update(glm::ivec2(200, 100), &planets, &[], bounds(), Physics::default());
+        slow.update(glm::ivec2(200, 100), &[], &[], bounds(), Physics::default());
+
+        assert!(rock.preview.iter().zip(&light.preview).all(|(r, l)| l.y > r.y));
+        assert_eq!(slow.preview,
+                   vec![glm::dvec2(103., 100.), glm::dvec2(106., 100.), glm::dvec2(109., 100.)]);
     }
 }
@@ -0,0 +1,551 @@
+use master_smasher::shape::{Circle, Intersect, Shape};
+use super::collidable::Collidable;
+use super::level_assets::LevelAssets;
+use super::level_data::{Edge, Gravity, LevelData, MeteorKind, Motion, ObjectData, PlanetKind};
+use super::planet::Planet;
+use super::star::Star;
+use errors::*;
+
+use glm;
+
+use std::cmp;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    ERROR,
+    WARNING,
+}
+
+/// A problem with a level, pointing at the entry it is about, e.g. `planets[2]`.
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The level file, if it was loaded from one.
+    pub file: Option<PathBuf>,
+    pub severity: Severity,
+    pub location: String,
+    /// Where in the window the entry is, if it is about a single body.
+    pub position: Option<glm::IVec2>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::ERROR => "error",
+            Severity::WARNING => "warning",
+        };
+        if let Some(ref file) = self.file {
+            write!(f, "{}: ", file.display())?;
+        }
+        write!(f, "{}: {}: {}", severity, self.location, self.message)
+    }
+}
+
+struct Validator {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    fn report(&mut self,
+              severity: Severity,
+              location: &str,
+              position: Option<glm::IVec2>,
+              message: String) {
+        self.diagnostics.push(Diagnostic {
+                                  file: None,
+                                  severity: severity,
+                                  location: location.to_string(),
+                                  position: position,
+                                  message: message,
+                              });
+    }
+
+    fn error(&mut self, location: &str, message: String) {
+        self.report(Severity::ERROR, location, None, message);
+    }
+
+    fn warning(&mut self, location: &str, message: String) {
+        self.report(Severity::WARNING, location, None, message);
+    }
+
+    fn error_at(&mut self, location: &str, position: glm::IVec2, message: String) {
+        self.report(Severity::ERROR, location, Some(position), message);
+    }
+
+    fn warning_at(&mut self, location: &str, position: glm::IVec2, message: String) {
+        self.report(Severity::WARNING, location, Some(position), message);
+    }
+
+    fn in_window(&mut self, location: &str, x: i32, y: i32, window_size: glm::UVec2) {
+        let size = glm::to_ivec2(window_size);
+        if x < 0 || y < 0 || x >= size.x || y >= size.y {
+            self.error_at(location,
+                          glm::ivec2(x, y),
+                          format!("({}, {}) is outside the {}x{} window",
+                                  x,
+                                  y,
+                                  size.x,
+                                  size.y));
+        }
+    }
+}
+
+/// Everything wrong with `data` that loading it wouldn't catch on its own. Errors make the
+/// level unplayable; warnings are probably mistakes.
+pub fn validate(data: &LevelData,
+                window_size: glm::UVec2,
+                assets: &LevelAssets)
+                -> Vec<Diagnostic> {
+    let mut v = Validator { diagnostics: vec![] };
+
+    if data.enemies.is_empty() {
+        v.error("enemies",
+                "there are no enemies, so the level is cleared before the first shot".to_string());
+    }
+    if data.shots == Some(0) {
+        v.error("shots", "a limit of 0 shots leaves the level unclearable".to_string());
+    }
+
+    let physics = [("physics.mass", data.physics.mass),
+                   ("physics.launch", data.physics.launch),
+                   ("physics.thrust", data.physics.thrust)];
+    for &(location, value) in &physics {
+        if value <= 0. {
+            v.error(location, format!("{} must be positive", value));
+        }
+    }
+
+    let edges = [("edges.left", data.edges.left),
+                 ("edges.right", data.edges.right),
+                 ("edges.top", data.edges.top),
+                 ("edges.bottom", data.edges.bottom)];
+    for &(location, edge) in &edges {
+        if let Edge::BOUNCE { restitution } = edge {
+            if restitution < 0. {
+                v.error(location,
+                        format!("restitution {} must not be negative", restitution));
+            } else if restitution > 1. {
+                v.warning(location,
+                          format!("restitution {} speeds the meteor up on every bounce",
+                                  restitution));
+            }
+        }
+    }
+
+    for (i, meteor) in data.meteors.iter().enumerate() {
+        let location = format!("meteors[{}]", i);
+        for &(name, value) in &[("mass", meteor.mass()), ("speed", meteor.speed())] {
+            if value <= 0. {
+                v.error(&location, format!("{} {} must be positive", name, value));
+            }
+        }
+        if let Some(restitution) = meteor.bouncy() {
+            if restitution < 0. {
+                v.error(&location,
+                        format!("bouncy restitution {} must not be negative", restitution));
+            } else if restitution > 1. {
+                v.warning(&location,
+                          format!("bouncy restitution {} speeds the meteor up on every bounce",
+                                  restitution));
+            }
+        }
+    }
+
+    // every type of meteor the level launches, or the plain rock if it doesn't say
+    let radius = |kind| {
+        let dims = assets.player.meteor(kind).dims;
+        cmp::min(dims.x, dims.y) as f64 / 2.
+    };
+    let launched: Vec<(Option<usize>, f64)> = if data.meteors.is_empty() {
+        vec![(None, radius(MeteorKind::ROCK))]
+    } else {
+        data.meteors.iter().enumerate().map(|(i, m)| (Some(i), radius(m.kind))).collect()
+    };
+    let meteor_at = glm::ivec2(data.meteor.x, data.meteor.y);
+    v.in_window("meteor", data.meteor.x, data.meteor.y, window_size);
+
+    let planets: Vec<Circle> = data.planets
+        .iter()
+        .map(|p| {
+                 Circle {
+                     center: glm::dvec2(p.x as f64, p.y as f64),
+                     radius: Planet::radius(p, &assets.world),
+                 }
+             })
+        .collect();
+
+    for (i, (data, body)) in data.planets.iter().zip(&planets).enumerate() {
+        let location = format!("planets[{}]", i);
+        let at = glm::ivec2(data.x, data.y);
+        v.in_window(&location, data.x, data.y, window_size);
+
+        match data.kind {
+            PlanetKind::RED { ring, strength } |
+            PlanetKind::BLUE { ring, strength } |
+            PlanetKind::WHITE { ring, strength } |
+            PlanetKind::PURPLE { ring, strength } => {
+                if ring <= 0. {
+                    v.error_at(&location, at, format!("ring radius {} must be positive", ring));
+                } else if ring <= body.radius {
+                    v.warning_at(&location,
+                                 at,
+                                 format!("ring radius {} is within the planet's own radius of \
+                                          {}, so it never pulls",
+                                         ring,
+                                         body.radius));
+                }
+                if strength < 0. {
+                    v.error_at(&location,
+                               at,
+                               format!("strength {} must not be negative", strength));
+                }
+            }
+            PlanetKind::DEAD => {}
+        }
+        match data.motion {
+            Some(Motion::ORBIT { period, .. }) if period == 0. => {
+                v.error_at(&location, at, "orbit period must not be 0".to_string());
+            }
+            Some(Motion::PATH { ref points, speed }) => {
+                if points.is_empty() {
+                    v.error_at(&location, at, "path has no points to move to".to_string());
+                }
+                if speed <= 0. {
+                    v.error_at(&location, at, format!("path speed {} must be positive", speed));
+                }
+                for (j, point) in points.iter().enumerate() {
+                    let location = format!("{}.motion.points[{}]", location, j);
+                    v.in_window(&location, point.x, point.y, window_size);
+                }
+            }
+            _ => {}
+        }
+        if let Some(restitution) = data.bouncy {
+            if restitution < 0. {
+                v.error_at(&location,
+                           at,
+                           format!("bouncy restitution {} must not be negative", restitution));
+            } else if restitution > 1. {
+                v.warning_at(&location,
+                             at,
+                             format!("bouncy restitution {} speeds the meteor up on every \
+                                      bounce",
+                                     restitution));
+            }
+        }
+        match data.gravity {
+            Gravity::POWER { exponent } |
+            Gravity::REPULSIVE { exponent } if exponent < 0. => {
+                v.warning_at(&location,
+                             at,
+                             format!("gravity exponent {} makes the pull grow with distance",
+                                     exponent));
+            }
+            _ => {}
+        }
+
+        for (j, other) in planets.iter().enumerate().skip(i + 1) {
+            if body.intersects(other) {
+                v.warning_at(&location, at, format!("overlaps planets[{}]", j));
+            }
+        }
+    }
+
+    let portal_texture = assets.world.portal;
+    let portal_radius = cmp::min(portal_texture.dims.x, portal_texture.dims.y) as f64 / 2.;
+    for (i, portal) in data.portals.iter().enumerate() {
+        let ends = [("a", &portal.a), ("b", &portal.b)];
+        let bodies: Vec<Circle> = ends.iter()
+            .map(|&(_, end)| {
+                     Circle {
+                         center: glm::dvec2(end.x as f64, end.y as f64),
+                         radius: portal_radius,
+                     }
+                 })
+            .collect();
+        for (&(name, end), body) in ends.iter().zip(&bodies) {
+            let location = format!("portals[{}].{}", i, name);
+            v.in_window(&location, end.x, end.y, window_size);
+            if body.contains(&glm::to_dvec2(meteor_at)) {
+                v.warning_at("meteor", meteor_at, format!("starts inside {}", location));
+            }
+            for (j, planet) in planets.iter().enumerate() {
+                if body.intersects(planet) {
+                    v.warning_at(&location,
+                                 glm::ivec2(end.x, end.y),
+                                 format!("overlaps planets[{}]", j));
+                }
+            }
+        }
+        if bodies[0].intersects(&bodies[1]) {
+            v.error_at(&format!("portals[{}]", i),
+                       glm::ivec2(portal.a.x, portal.a.y),
+                       "its ends overlap, so the meteor would go back and forth".to_string());
+        }
+    }
+
+    check_launch(&mut v, "meteor", meteor_at, &launched, &planets);
+    for (i, station) in data.stations.iter().enumerate() {
+        let location = format!("stations[{}]", i);
+        v.in_window(&location, station.x, station.y, window_size);
+        check_launch(&mut v, &location, station.into(), &launched, &planets);
+    }
+
+    check_targets(&mut v, "stars", &data.stars, &planets, window_size, |c| {
+        Star::new(assets.world.star(c))
+    });
+    check_targets(&mut v, "enemies", &data.enemies, &planets, window_size, |c| {
+        Star::new(assets.world.enemy(c))
+    });
+
+    v.diagnostics
+}
+
+/// Errors for each planet that one of the `launched` meteor radii would start inside of at `at`.
+fn check_launch(v: &mut Validator,
+                location: &str,
+                at: glm::IVec2,
+                launched: &[(Option<usize>, f64)],
+                planets: &[Circle]) {
+    for (j, planet) in planets.iter().enumerate() {
+        let inside = launched.iter().find(|&&(_, radius)| {
+            let body = Circle {
+                center: glm::to_dvec2(at),
+                radius: radius,
+            };
+            body.intersects(planet)
+        });
+        match inside {
+            Some(&(Some(i), _)) => {
+                v.error_at(location,
+                           at,
+                           format!("starts inside planets[{}] as meteors[{}]", j, i));
+            }
+            Some(&(None, _)) => v.error_at(location, at, format!("starts inside planets[{}]", j)),
+            None => {}
+        }
+    }
+}
+
+fn check_targets<F>(v: &mut Validator,
+                    list: &str,
+                    targets: &[ObjectData],
+                    planets: &[Circle],
+                    window_size: glm::UVec2,
+                    load: F)
+    where F: Fn(glm::IVec2) -> Star
+{
+    for (i, target) in targets.iter().enumerate() {
+        let location = format!("{}[{}]", list, i);
+        v.in_window(&location, target.x, target.y, window_size);
+
+        let star = load(target.into());
+        for (j, planet) in planets.iter().enumerate() {
+            if star.collides(planet) {
+                v.warning_at(&location,
+                             glm::ivec2(target.x, target.y),
+                             format!("overlaps planets[{}]", j));
+            }
+        }
+    }
+}
+
+/// Loads a level and refuses it if it has any errors, otherwise returning it along with its
+/// warnings for the caller to show.
+pub fn load_valid<P>(path: P,
+                     window_size: glm::UVec2,
+                     assets: &LevelAssets)
+                     -> Result<(LevelData, Vec<Diagnostic>)>
+    where P: AsRef<Path>
+{
+    let path = path.as_ref();
+    let data = LevelData::load(path)?;
+    let diagnostics: Vec<Diagnostic> = validate(&data, window_size, assets)
+        .into_iter()
+        .map(|d| Diagnostic { file: Some(path.to_path_buf()), ..d })
+        .collect();
+    let errors: Vec<String> = diagnostics.iter()
+        .filter(|d| d.severity == Severity::ERROR)
+        .map(|d| d.to_string())
+        .collect();
+    if !errors.is_empty() {
+        bail!("not a valid level:\n{}", errors.join("\n"));
+    }
+    Ok((data, diagnostics))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::HeadlessLoader;
+    use master_smasher::level::level_data::PortalData;
+    use serde_yaml;
+
+    fn diagnostics(data: &LevelData) -> Vec<String> {
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        validate(data, glm::uvec2(1280, 720), &assets)
+            .iter()
+            .map(|d| d.to_string())
+            .collect()
+    }
+
+    fn level_1() -> LevelData {
+        LevelData::load("levels/level_1.lvl").unwrap()
+    }
+
+    #[test]
+    fn shipped_level_is_clean() {
+        assert_eq!(diagnostics(&level_1()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn meteor_inside_planet() {
+        let mut data = level_1();
+        data.meteor.x = data.planets[1].x + 10;
+        data.meteor.y = data.planets[1].y;
+        assert_eq!(diagnostics(&data), vec!["error: meteor: starts inside planets[1]"]);
+    }
+
+    #[test]
+    fn points_at_bodies() {
+        let mut data = level_1();
+        data.meteor.x = data.planets[1].x + 10;
+        data.meteor.y = data.planets[1].y;
+        data.shots = Some(0);
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let positions: Vec<_> = validate(&data, glm::uvec2(1280, 720), &assets)
+            .iter()
+            .map(|d| d.position)
+            .collect();
+        assert_eq!(positions, vec![None, Some(glm::ivec2(data.meteor.x, data.meteor.y))]);
+    }
+
+    #[test]
+    fn outside_window() {
+        let mut data = level_1();
+        data.stars[2].x = 1300;
+        assert_eq!(diagnostics(&data),
+                   vec!["error: stars[2]: (1300, 50) is outside the 1280x720 window"]);
+    }
+
+    #[test]
+    fn negative_ring() {
+        let mut data = level_1();
+        data.planets[0].kind = PlanetKind::WHITE {
+            ring: -5.,
+            strength: 400.,
+        };
+        assert_eq!(diagnostics(&data),
+                   vec!["error: planets[0]: ring radius -5 must be positive"]);
+    }
+
+    #[test]
+    fn no_enemies() {
+        let mut data = level_1();
+        data.enemies.clear();
+        assert_eq!(diagnostics(&data),
+                   vec!["error: enemies: there are no enemies, so the level is cleared before \
+                         the first shot"]);
+    }
+
+    #[test]
+    fn enemy_on_planet_warns() {
+        let mut data = level_1();
+        data.enemies[0].x = data.planets[2].x;
+        data.enemies[0].y = data.planets[2].y;
+        assert_eq!(diagnostics(&data), vec!["warning: enemies[0]: overlaps planets[2]"]);
+    }
+
+    #[test]
+    fn bouncy_edges() {
+        let mut data = level_1();
+        data.edges.left = Edge::BOUNCE { restitution: -1. };
+        data.edges.top = Edge::BOUNCE { restitution: 1.5 };
+        assert_eq!(diagnostics(&data),
+                   vec!["error: edges.left: restitution -1 must not be negative",
+                        "warning: edges.top: restitution 1.5 speeds the meteor up on every \
+                         bounce"]);
+    }
+
+    #[test]
+    fn physics_and_gravity() {
+        let mut data = level_1();
+        data.physics.mass = 0.;
+        data.planets[1].gravity = Gravity::POWER { exponent: -1. };
+        assert_eq!(diagnostics(&data),
+                   vec!["error: physics.mass: 0 must be positive",
+                        "warning: planets[1]: gravity exponent -1 makes the pull grow with \
+                         distance"]);
+    }
+
+    #[test]
+    fn negative_bounce() {
+        let mut data = level_1();
+        data.planets[2].bouncy = Some(-0.5);
+        assert_eq!(diagnostics(&data),
+                   vec!["error: planets[2]: bouncy restitution -0.5 must not be negative"]);
+    }
+
+    #[test]
+    fn path_leaves_window() {
+        let mut data = level_1();
+        data.planets[2].motion = Some(Motion::PATH {
+                                          points: vec![ObjectData { x: 496, y: 800 }],
+                                          speed: 2.,
+                                      });
+        assert_eq!(diagnostics(&data),
+                   vec!["error: planets[2].motion.points[0]: (496, 800) is outside the 1280x720 \
+                         window"]);
+    }
+
+    #[test]
+    fn portal_ends() {
+        let mut data = level_1();
+        data.portals = vec![PortalData {
+                                a: ObjectData { x: 1000, y: 100 },
+                                b: ObjectData { x: 1300, y: 100 },
+                                rotation: 0.,
+                            },
+                            PortalData {
+                                a: ObjectData { x: 1000, y: 300 },
+                                b: ObjectData { x: 1020, y: 320 },
+                                rotation: 0.,
+                            }];
+        assert_eq!(diagnostics(&data),
+                   vec!["error: portals[0].b: (1300, 100) is outside the 1280x720 window",
+                        "error: portals[1]: its ends overlap, so the meteor would go back and \
+                         forth"]);
+    }
+
+    #[test]
+    fn station_inside_planet() {
+        let mut data = level_1();
+        data.stations = vec![ObjectData { x: 100, y: 100 }, ObjectData { x: 496, y: 540 }];
+        assert_eq!(diagnostics(&data),
+                   vec!["error: stations[1]: starts inside planets[2]"]);
+    }
+
+    #[test]
+    fn station_inside_planet_as_larger_meteor() {
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let mut data = level_1();
+        let planet = &data.planets[1];
+        let x = planet.x + assets.planet_radius(planet) as i32 + 33;
+        data.stations = vec![ObjectData { x: x, y: planet.y }];
+        data.meteors = serde_yaml::from_str("[{kind: ROCK}, {kind: HEAVY}]").unwrap();
+        assert_eq!(diagnostics(&data),
+                   vec!["error: stations[0]: starts inside planets[1] as meteors[1]"]);
+    }
+
+    #[test]
+    fn meteor_types() {
+        let mut data = level_1();
+        data.meteors = serde_yaml::from_str("[{kind: HEAVY, mass: 0}, {bouncy: 1.5}]").unwrap();
+        assert_eq!(diagnostics(&data),
+                   vec!["error: meteors[0]: mass 0 must be positive",
+                        "warning: meteors[1]: bouncy restitution 1.5 speeds the meteor up on \
+                         every bounce"]);
+    }
+}
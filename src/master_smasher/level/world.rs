@@ -1,19 +1,22 @@
 use super::level_data::LevelData;
 use super::launched_meteor::LaunchedMeteor;
 use super::planet::Planet;
+use super::portal::Portal;
 use super::star::Star;
 use super::world_assets::WorldAssets;
 use master_smasher::drawable::{Animation, AnimationData, TryIterator};
 
 use glm;
-use moho::resource_manager::{Renderer, Scene};
+use moho::resource_manager::Renderer;
 use moho::errors as moho_errors;
 use num_traits::One;
+use sdl2::rect;
 
 use std::time::Duration;
 
 pub struct World {
     pub planets: Vec<Planet>,
+    pub portals: Vec<Portal>,
     pub stars: Vec<Star>,
     pub enemies: Vec<Star>,
     pub explosions: Vec<Animation>,
@@ -26,6 +29,10 @@ impl World {
             .iter()
             .map(|p| Planet::new(p, &assets))
             .collect();
+        let portals = data.portals
+            .iter()
+            .map(|p| Portal::new(p, assets.portal))
+            .collect();
         let stars = data.stars
             .iter()
             .map(|s| Star::new(assets.star(s.into())))
@@ -37,6 +44,7 @@ impl World {
 
         World {
             planets: planets,
+            portals: portals,
             stars: stars,
             enemies: enemies,
             explosions: Vec::new(),
@@ -44,7 +52,16 @@ impl World {
         }
     }
 
-    pub fn collide(&mut self, meteor: &LaunchedMeteor) {
+    pub fn update(&mut self) {
+        for planet in &mut self.planets {
+            planet.update();
+        }
+    }
+
+    /// Destroys the stars and enemies the meteor hit in the last tick, returning whether there
+    /// were any.
+    pub fn collide(&mut self, meteor: &LaunchedMeteor) -> bool {
+        let targets = self.stars.len() + self.enemies.len();
         let explosion = &self.explosion_data;
         let explosions = &mut self.explosions;
 
@@ -65,6 +82,8 @@ impl World {
         } else {
             true
         });
+
+        self.stars.len() + self.enemies.len() < targets
     }
 
     pub fn animate(&mut self, delta: Duration) {
@@ -86,13 +105,28 @@ impl World {
 
         self.explosions.retain(Animation::is_active);
     }
-}
 
-impl Scene for World {
-    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
-        self.planets
+    pub fn outline(&self, rects: &mut Vec<rect::Rect>) {
+        for planet in &self.planets {
+            planet.outline(rects);
+        }
+        for portal in &self.portals {
+            portal.outline(rects);
+        }
+        for star in self.stars.iter().chain(&self.enemies) {
+            star.outline(rects);
+        }
+    }
+
+    pub fn draw<R>(&self, interpolation: f64, renderer: &mut R) -> moho_errors::Result<()>
+        where R: Renderer
+    {
+        self.portals
             .iter()
             .try(|d| renderer.show(d))?;
+        self.planets
+            .iter()
+            .try(|p| p.draw(interpolation, renderer))?;
         self.enemies
             .iter()
             .try(|d| renderer.show(d))?;
@@ -13,7 +13,10 @@ pub struct WorldAssets {
     pub red_ring: Texture,
     pub white_ring: Texture,
     pub blue_ring: Texture,
+    pub purple_planet: Texture,
+    pub purple_ring: Texture,
     pub dead_planet: Texture,
+    pub portal: Texture,
 
     pub star: AnimationData,
     pub explosion: AnimationData,
@@ -29,6 +32,9 @@ impl WorldAssets {
         let red_ring = resource_loader.load_texture("resources/red_ring.png")?;
         let white_ring = resource_loader.load_texture("resources/white_ring.png")?;
         let blue_ring = resource_loader.load_texture("resources/blue_ring.png")?;
+        let purple_planet = resource_loader.load_texture("resources/purple_planet.png")?;
+        let purple_ring = resource_loader.load_texture("resources/purple_ring.png")?;
+        let portal = resource_loader.load_texture("resources/portal.png")?;
 
         let star_path = "resources/star.png";
         let enemy_path = "resources/spaceship.png";
@@ -44,6 +50,9 @@ impl WorldAssets {
             red_ring: red_ring,
             white_ring: white_ring,
             blue_ring: blue_ring,
+            purple_planet: purple_planet,
+            purple_ring: purple_ring,
+            portal: portal,
             star: star,
             enemy: enemy,
             explosion: explosion,
@@ -1,43 +1,114 @@
+mod campaign;
 mod drawable;
+mod frame_counter;
+mod generator;
+mod headless_loader;
 mod level;
+mod progress;
+mod replay;
+mod save;
 mod shape;
+mod solver;
+mod state;
 
-use self::level::Level;
+pub use self::generator::Generator;
+pub use self::headless_loader::HeadlessLoader;
+pub use self::level::{LevelAssets, LevelData, Severity, Simulation, load_valid, validate};
+pub use self::replay::Replay;
+pub use self::solver::Solver;
+
+use self::campaign::Campaign;
+use self::drawable::digits;
+use self::frame_counter::FrameCounter;
+use self::level::Overlay;
+use self::progress::Progress;
+use self::state::{Context, Editor, Gameplay, Menu, ResultsAssets, State, StateStack,
+                  Transition};
 
 use errors::*;
+use glm;
 use moho::input_manager::InputManager;
-use moho::resource_manager::{Renderer, ResourceLoader, ResourceManager, TextureId};
+use moho::resource_manager::{Renderer, ResourceManager};
 use moho::timer::Timer;
 use moho::MohoEngine;
 use sdl2::keyboard::Keycode;
 
+use std::mem;
+use std::path::Path;
 use std::time::Duration;
 
+pub const GAME_SPEED: u32 = 60;
+
 pub struct MasterSmasher<E: MohoEngine> {
-    level: Level,
-    background: TextureId,
+    context: Context,
+    states: StateStack,
     input_manager: InputManager<E::EventPump>,
     renderer: ResourceManager<E::Renderer>,
+    /// F3 toggles the collision shapes, forces and frame rate, F4 the gravity field.
+    overlay: Overlay,
+    counter: FrameCounter,
 }
 
 impl<E: MohoEngine> MasterSmasher<E> {
-    pub fn new(mut renderer: ResourceManager<E::Renderer>,
+    pub fn new(renderer: ResourceManager<E::Renderer>,
                input_manager: InputManager<E::EventPump>)
                -> Result<Self> {
-        let background = renderer.load_texture("resources/background_game.png")?;
         let window_size = renderer.output_size()?;
-        renderer.wrap_coords = Some(window_size);
-        let level = Level::load("levels/level_1.lvl", window_size, &renderer)?;
+        let mut save_path = Progress::default_path();
+        let mut warnings = vec![];
+        let progress = match save_path.as_ref().map(Progress::load_or_default) {
+            Some(Ok((progress, warning))) => {
+                warnings.extend(warning);
+                progress
+            }
+            Some(Err(e)) => {
+                warnings.push(format!("playing without saving, the save could not be read: {}", e));
+                save_path = None;
+                Progress::default()
+            }
+            None => Progress::default(),
+        };
+        let context = Context {
+            campaign: Campaign::load("levels/campaign.yaml")?,
+            progress: progress,
+            save_path: save_path,
+            replay_dir: save::data_dir().map(|d| d.join("replays")),
+            level_assets: LevelAssets::new(&renderer)?,
+            results_assets: ResultsAssets::new(&renderer)?,
+            window_size: window_size,
+            warnings: warnings,
+        };
+        let menu = Menu::new(&renderer, window_size)?;
         Ok(MasterSmasher {
-               level: level,
-               background: background.id,
+               context: context,
+               states: StateStack::new(State::MENU(menu)),
                input_manager: input_manager,
                renderer: renderer,
+               overlay: Overlay::default(),
+               counter: FrameCounter::new(),
            })
     }
 
+    /// Plays `replay` back on top of the menu.
+    pub fn watch(&mut self, replay: &Replay) -> Result<()> {
+        let gameplay = Gameplay::replay(replay, &mut self.context, &self.renderer)?;
+        self.states.apply(Transition::PUSH(State::PLAYING(gameplay)));
+        Ok(())
+    }
+
+    /// Opens the level at `path` in the editor on top of the menu.
+    pub fn edit<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let editor = Editor::load(path, &self.context, &self.renderer)?;
+        self.states.apply(Transition::PUSH(State::EDITOR(editor)));
+        Ok(())
+    }
+
+    /// Takes the warnings gathered so far.
+    pub fn warnings(&mut self) -> Vec<String> {
+        mem::replace(&mut self.context.warnings, vec![])
+    }
+
     pub fn run(&mut self) -> Result<()> {
-        const GAME_SPEED: u32 = 60;
         const MAX_SKIP: u32 = 10;
         let update_duration = Duration::new(0, 1000000000 / GAME_SPEED);
         let mut timer = Timer::new();
@@ -51,33 +122,59 @@ impl<E: MohoEngine> MasterSmasher<E> {
                 if self.game_quit() {
                     break;
                 }
-                self.update();
+                self.update()?;
+                self.counter.update();
                 delta -= update_duration;
                 loops += 1;
             }
             if self.game_quit() {
                 break;
             }
-            self.level.animate(game_time.since_update);
+            self.animate(game_time.since_update);
+            self.counter.frame(game_time.since_update);
             let interpolation = delta.subsec_nanos() as f64 / update_duration.subsec_nanos() as f64;
             self.draw(interpolation)?;
         }
         Ok(())
     }
 
-    fn update(&mut self) {
-        self.level.update(&self.input_manager);
+    fn update(&mut self) -> Result<()> {
+        if self.input_manager.did_press_key(Keycode::F3) {
+            self.overlay.shapes = !self.overlay.shapes;
+        }
+        if self.input_manager.did_press_key(Keycode::F4) {
+            self.overlay.field = !self.overlay.field;
+        }
+        self.states.update(&self.input_manager, &mut self.context, &self.renderer)
+    }
+
+    fn animate(&mut self, delta: Duration) {
+        self.states.animate(delta);
     }
 
     fn draw(&mut self, interpolation: f64) -> Result<()> {
         self.renderer.clear();
-        self.renderer.show(&self.background)?;
-        self.level.draw(interpolation, &mut self.renderer)?;
+        self.states.draw(interpolation, &mut self.renderer)?;
+        self.states.draw_overlay(self.overlay, &mut self.renderer)?;
+        if self.overlay.shapes {
+            self.draw_frame_rate()?;
+        }
         self.renderer.present();
         Ok(())
     }
 
+    /// Frames per second over updates per second in the top left corner.
+    fn draw_frame_rate(&mut self) -> Result<()> {
+        const DIGIT_WIDTH: u32 = 10;
+        let mut rects = vec![];
+        digits::number(self.counter.fps, glm::ivec2(10, 10), DIGIT_WIDTH, &mut rects);
+        digits::number(self.counter.ups, glm::ivec2(10, 40), DIGIT_WIDTH, &mut rects);
+        self.renderer.fill_rects(&rects)?;
+        Ok(())
+    }
+
     fn game_quit(&self) -> bool {
-        self.input_manager.game_quit() || self.input_manager.is_key_down(Keycode::Escape)
+        self.input_manager.game_quit() || self.input_manager.is_key_down(Keycode::Escape) ||
+        self.states.is_empty()
     }
 }
@@ -0,0 +1,196 @@
+use master_smasher::campaign::Campaign;
+use master_smasher::level::Score;
+use master_smasher::save;
+use errors::*;
+
+use serde_yaml;
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LevelRecord {
+    #[serde(default)]
+    pub cleared: bool,
+    #[serde(default)]
+    pub stars: u32,
+    #[serde(default)]
+    pub best_shots: Option<u32>,
+    /// Fastest clear, in game updates.
+    #[serde(default)]
+    pub best_ticks: Option<u32>,
+}
+
+/// What the player has achieved in each level, keyed by level file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Progress {
+    #[serde(default)]
+    levels: HashMap<String, LevelRecord>,
+}
+
+impl Progress {
+    /// Where progress is saved between runs, if the platform has a data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        save::data_dir().map(|d| d.join("progress.yaml"))
+    }
+
+    /// Missing files mean a fresh start. Ones that don't parse are moved aside to
+    /// `<path>.corrupt` and also start fresh, with a warning saying so, so a bad save never
+    /// keeps the game from starting. Any other error is returned and the file left alone, as
+    /// it may still hold progress that saving over it would lose.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Result<(Progress, Option<String>)> {
+        let path = path.as_ref();
+        match Progress::load(path) {
+            Ok(p) => Ok((p, None)),
+            Err(Error(ErrorKind::Io(ref e), _)) if e.kind() == io::ErrorKind::NotFound => {
+                Ok((Progress::default(), None))
+            }
+            Err(Error(ErrorKind::Yaml(e), _)) => {
+                let mut backup = path.as_os_str().to_owned();
+                backup.push(".corrupt");
+                fs::rename(path, &backup)?;
+                let warning = format!("moved unreadable save {} aside: {}", path.display(), e);
+                Ok((Progress::default(), Some(warning)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Progress> {
+        let mut yaml = vec![];
+        File::open(path)?.read_to_end(&mut yaml)?;
+        serde_yaml::from_slice(&yaml).map_err(Into::into)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let yaml = serde_yaml::to_string(self)?;
+        save::write_atomically(path, yaml.as_bytes())
+    }
+
+    pub fn record_clear(&mut self, level: &Path, score: Score) {
+        let record = self.levels.entry(Self::key(level)).or_insert_with(Default::default);
+        record.cleared = true;
+        record.stars = record.stars.max(score.stars);
+        record.best_shots = Some(record.best_shots.map_or(score.shots, |s| s.min(score.shots)));
+        record.best_ticks = Some(record.best_ticks.map_or(score.ticks, |t| t.min(score.ticks)));
+    }
+
+    pub fn get(&self, level: &Path) -> LevelRecord {
+        self.levels.get(&Self::key(level)).cloned().unwrap_or_default()
+    }
+
+    /// The first level is always open; every other one opens once the one before is cleared.
+    pub fn is_unlocked(&self, campaign: &Campaign, index: usize) -> bool {
+        index == 0 || campaign.level(index - 1).map_or(false, |l| self.get(l).cleared)
+    }
+
+    fn key(level: &Path) -> String {
+        level.to_string_lossy().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+    use std::io::Write;
+    use std::process;
+
+    fn score(shots: u32, stars: u32, ticks: u32) -> Score {
+        Score {
+            shots: shots,
+            stars: stars,
+            total_stars: 3,
+            ticks: ticks,
+        }
+    }
+
+    /// A directory of its own for each test, as they run in parallel.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("master_smasher_progress_{}_{}",
+                                               name,
+                                               process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn keeps_best_of_each() {
+        let mut progress = Progress::default();
+        let level = Path::new("levels/level_1.lvl");
+        progress.record_clear(level, score(2, 3, 500));
+        progress.record_clear(level, score(1, 1, 700));
+        assert_eq!(progress.get(level),
+                   LevelRecord {
+                       cleared: true,
+                       stars: 3,
+                       best_shots: Some(1),
+                       best_ticks: Some(500),
+                   });
+    }
+
+    #[test]
+    fn unlocks_after_previous_clear() {
+        let campaign = Campaign::load("levels/campaign.yaml").unwrap();
+        let mut progress = Progress::default();
+        assert!(progress.is_unlocked(&campaign, 0));
+        assert!(!progress.is_unlocked(&campaign, 1));
+
+        progress.record_clear(campaign.level(0).unwrap(), score(1, 0, 100));
+        assert!(progress.is_unlocked(&campaign, 1));
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let dir = temp_dir("round_trip");
+        let path = dir.join("progress.yaml");
+        let level = Path::new("levels/level_2.lvl");
+        let mut progress = Progress::default();
+        progress.record_clear(level, score(2, 2, 300));
+        progress.save(&path).unwrap();
+
+        let (loaded, warning) = Progress::load_or_default(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.get(level), progress.get(level));
+        assert_eq!(warning, None);
+    }
+
+    #[test]
+    fn missing_file_starts_fresh() {
+        let dir = temp_dir("missing");
+        let (progress, warning) = Progress::load_or_default(dir.join("progress.yaml")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(progress.levels.len(), 0);
+        assert_eq!(warning, None);
+    }
+
+    #[test]
+    fn corrupt_file_is_set_aside() {
+        let dir = temp_dir("corrupt");
+        let path = dir.join("progress.yaml");
+        File::create(&path).unwrap().write_all(b"levels: [unterminated").unwrap();
+
+        let (progress, warning) = Progress::load_or_default(&path).unwrap();
+        let (kept, set_aside) = (path.exists(), dir.join("progress.yaml.corrupt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(progress.levels.len(), 0);
+        assert!(warning.is_some());
+        assert!(!kept && set_aside);
+    }
+
+    #[test]
+    fn unreadable_file_is_kept() {
+        let dir = temp_dir("unreadable");
+        let path = dir.join("progress.yaml");
+        // a directory opens but can't be read
+        fs::create_dir(&path).unwrap();
+
+        let loaded = Progress::load_or_default(&path);
+        let kept = path.exists();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(loaded.is_err());
+        assert!(kept);
+    }
+}
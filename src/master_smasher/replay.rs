@@ -0,0 +1,228 @@
+use master_smasher::level::PlayerInput;
+use master_smasher::save;
+use errors::*;
+
+use glm;
+use serde_yaml;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the format or the simulation changes in a way old replays can't survive.
+pub const REPLAY_VERSION: u32 = 3;
+
+/// The same input held for `repeat` consecutive ticks.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Frame {
+    repeat: u32,
+    x: i32,
+    y: i32,
+    #[serde(default)]
+    launch: bool,
+    #[serde(default)]
+    abort: bool,
+    #[serde(default)]
+    restart: bool,
+    #[serde(default)]
+    thrust_x: i32,
+    #[serde(default)]
+    thrust_y: i32,
+    #[serde(default)]
+    steer: bool,
+    #[serde(default)]
+    switch: bool,
+}
+
+impl Frame {
+    fn new(input: &PlayerInput) -> Self {
+        Frame {
+            repeat: 1,
+            x: input.target.x,
+            y: input.target.y,
+            launch: input.launch,
+            abort: input.abort,
+            restart: input.restart,
+            thrust_x: input.thrust.x,
+            thrust_y: input.thrust.y,
+            steer: input.steer,
+            switch: input.switch,
+        }
+    }
+
+    fn input(&self) -> PlayerInput {
+        PlayerInput {
+            target: glm::ivec2(self.x, self.y),
+            launch: self.launch,
+            abort: self.abort,
+            restart: self.restart,
+            thrust: glm::ivec2(self.thrust_x, self.thrust_y),
+            steer: self.steer,
+            switch: self.switch,
+        }
+    }
+}
+
+/// Every input fed to a level, tick by tick, so a run can be played back exactly.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    level: PathBuf,
+    frames: Vec<Frame>,
+}
+
+impl Replay {
+    pub fn new<P: AsRef<Path>>(level: P) -> Self {
+        Replay {
+            version: REPLAY_VERSION,
+            level: level.as_ref().to_path_buf(),
+            frames: vec![],
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay> {
+        let path = path.as_ref();
+        let f = File::open(path)?;
+        let replay: Replay = serde_yaml::from_reader(&f)?;
+        if replay.version != REPLAY_VERSION {
+            bail!("replay {} is version {}, expected {}",
+                  path.display(),
+                  replay.version,
+                  REPLAY_VERSION);
+        }
+        Ok(replay)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let yaml = serde_yaml::to_string(self)?;
+        save::write_atomically(path, yaml.as_bytes())
+    }
+
+    pub fn level(&self) -> &Path {
+        &self.level
+    }
+
+    pub fn record(&mut self, input: &PlayerInput) {
+        if let Some(last) = self.frames.last_mut() {
+            if last.input() == *input {
+                last.repeat += 1;
+                return;
+            }
+        }
+        self.frames.push(Frame::new(input));
+    }
+
+    /// One input per tick, in order.
+    pub fn inputs(&self) -> Vec<PlayerInput> {
+        self.frames
+            .iter()
+            .flat_map(|f| (0..f.repeat).map(move |_| f.input()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::{HeadlessLoader, LevelAssets, LevelData, Simulation};
+    use master_smasher::level::Score;
+
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::process;
+
+    /// A directory of its own for each test, as they run in parallel.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("master_smasher_replay_{}_{}",
+                                               name,
+                                               process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn one_shot() -> Replay {
+        let target = glm::ivec2(150, 120);
+        let mut replay = Replay::new("levels/level_1.lvl");
+        replay.record(&PlayerInput::aim(target));
+        replay.record(&PlayerInput::launch(target));
+        for _ in 0..400 {
+            replay.record(&PlayerInput::aim(target));
+        }
+        replay
+    }
+
+    fn play(replay: &Replay) -> Simulation {
+        let data = LevelData::load(replay.level()).unwrap();
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        let mut simulation = Simulation::new(data, glm::uvec2(1280, 720), assets);
+        for input in replay.inputs() {
+            simulation.step(&input);
+        }
+        simulation
+    }
+
+    #[test]
+    fn merges_repeated_inputs() {
+        let replay = one_shot();
+        assert_eq!(replay.frames.len(), 3);
+        assert_eq!(replay.inputs().len(), 402);
+        assert_eq!(replay.inputs()[1], PlayerInput::launch(glm::ivec2(150, 120)));
+    }
+
+    #[test]
+    fn playback_reproduces_outcome() {
+        let replay = one_shot();
+        let first = play(&replay);
+        let second = play(&replay);
+        assert_eq!((first.outcome(), first.enemies_left(), first.stars_left()), (None, 2, 2));
+        assert_eq!(first.score(),
+                   Score {
+                       shots: 1,
+                       stars: 1,
+                       total_stars: 3,
+                       ticks: 402,
+                   });
+        assert_eq!(first.score(), second.score());
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let dir = temp_dir("round_trip");
+        let path = dir.join("replay.yaml");
+        let replay = one_shot();
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded, replay);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let dir = temp_dir("old_version");
+        let path = dir.join("replay.yaml");
+        File::create(&path)
+            .unwrap()
+            .write_all(b"version: 0\nlevel: levels/level_1.lvl\nframes: []\n")
+            .unwrap();
+        let loaded = Replay::load(&path);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn keeps_thrust() {
+        let dir = temp_dir("thrust");
+        let path = dir.join("replay.yaml");
+        let input = PlayerInput {
+            thrust: glm::ivec2(1, -1),
+            steer: true,
+            ..PlayerInput::aim(glm::ivec2(10, 20))
+        };
+        let mut replay = Replay::new("levels/level_1.lvl");
+        replay.record(&input);
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.inputs(), vec![input]);
+    }
+}
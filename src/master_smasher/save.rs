@@ -0,0 +1,64 @@
+use errors::*;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const GAME_DIR: &'static str = "master_smasher";
+
+/// Per-user directory the game keeps its files in, following each platform's convention.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|h| Path::new(&h).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|h| Path::new(&h).join(".local/share")))
+    };
+    base.map(|b| b.join(GAME_DIR))
+}
+
+/// Writes to a sibling temporary file first and renames it over `path`, so a crash mid-write
+/// never leaves a truncated file behind.
+pub fn write_atomically<P: AsRef<Path>>(path: P, contents: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    {
+        let mut f = File::create(&temp)?;
+        f.write_all(contents)?;
+        f.sync_all()?;
+    }
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+    use std::process;
+
+    #[test]
+    fn replaces_existing_file() {
+        let dir = env::temp_dir().join(format!("master_smasher_save_{}", process::id()));
+        let path = dir.join("write_atomically.txt");
+        write_atomically(&path, b"first").unwrap();
+        write_atomically(&path, b"second").unwrap();
+
+        let mut contents = String::new();
+        File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+        let temp_left = path.with_extension("txt.tmp").exists();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(contents, "second");
+        assert!(!temp_left);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Intersect, Line, Shape, Rectangle};
+use super::{Collide, Contact, Intersect, Line, Shape, Rectangle};
 
 use glm;
 
@@ -33,6 +33,61 @@ impl Intersect<Circle> for Circle {
     }
 }
 
+impl Collide<Circle> for Circle {
+    fn contact(&self, other: &Circle) -> Option<Contact> {
+        let offset = self.center - other.center;
+        let distance = glm::length(offset);
+        let radii = self.radius + other.radius;
+        if distance > radii {
+            return None;
+        }
+
+        // concentric circles can be pushed apart in any direction
+        let normal = if distance > 0_f64 {
+            offset / distance
+        } else {
+            glm::dvec2(0_f64, -1_f64)
+        };
+        Some(Contact {
+                 normal: normal,
+                 depth: radii - distance,
+             })
+    }
+}
+
+impl Collide<Rectangle> for Circle {
+    fn contact(&self, other: &Rectangle) -> Option<Contact> {
+        let half = other.dims / 2_f64;
+        let min = other.center - half;
+        let max = other.center + half;
+        let closest = glm::dvec2(self.center.x.max(min.x).min(max.x),
+                                 self.center.y.max(min.y).min(max.y));
+        let offset = self.center - closest;
+        let distance = glm::length(offset);
+        if distance > self.radius {
+            return None;
+        }
+        if distance > 0_f64 {
+            return Some(Contact {
+                            normal: offset / distance,
+                            depth: self.radius - distance,
+                        });
+        }
+
+        // the center is inside, so leave through the nearest side
+        let sides = [(self.center.x - min.x, glm::dvec2(-1_f64, 0_f64)),
+                     (max.x - self.center.x, glm::dvec2(1_f64, 0_f64)),
+                     (self.center.y - min.y, glm::dvec2(0_f64, -1_f64)),
+                     (max.y - self.center.y, glm::dvec2(0_f64, 1_f64))];
+        let &(inside, normal) = sides.iter()
+            .fold(&sides[0], |nearest, side| if side.0 < nearest.0 { side } else { nearest });
+        Some(Contact {
+                 normal: normal,
+                 depth: self.radius + inside,
+             })
+    }
+}
+
 impl Intersect<Line> for Circle {
     fn intersects(&self, other: &Line) -> bool {
         let length = other.1 - other.0;
@@ -170,4 +225,53 @@ mod test {
 
         assert!(circle.intersects(&rectangle));
     }
+
+    #[test]
+    fn circle_circle_contact() {
+        let circle_a = Circle {
+            radius: 2_f64,
+            center: glm::dvec2(3_f64, 0_f64),
+        };
+
+        let circle_b = Circle {
+            radius: 2_f64,
+            center: glm::dvec2(0_f64, 0_f64),
+        };
+
+        assert_eq!(circle_a.contact(&circle_b),
+                   Some(Contact {
+                            normal: glm::dvec2(1_f64, 0_f64),
+                            depth: 1_f64,
+                        }));
+        assert_eq!(Circle { center: glm::dvec2(5_f64, 0_f64), ..circle_a }.contact(&circle_b),
+                   None);
+    }
+
+    #[test]
+    fn rectangle_contact() {
+        let rectangle = Rectangle {
+            dims: glm::dvec2(4_f64, 2_f64),
+            center: glm::dvec2(0_f64, 0_f64),
+        };
+
+        let above = Circle {
+            radius: 1_f64,
+            center: glm::dvec2(1_f64, -1.5_f64),
+        };
+        assert_eq!(above.contact(&rectangle),
+                   Some(Contact {
+                            normal: glm::dvec2(0_f64, -1_f64),
+                            depth: 0.5_f64,
+                        }));
+
+        let inside = Circle {
+            radius: 1_f64,
+            center: glm::dvec2(1.5_f64, 0_f64),
+        };
+        assert_eq!(inside.contact(&rectangle),
+                   Some(Contact {
+                            normal: glm::dvec2(1_f64, 0_f64),
+                            depth: 1.5_f64,
+                        }));
+    }
 }
@@ -1,8 +1,10 @@
 pub mod circle;
 pub mod rectangle;
+pub mod swept_circle;
 
 pub use self::circle::Circle;
 pub use self::rectangle::Rectangle;
+pub use self::swept_circle::SweptCircle;
 
 use glm;
 
@@ -12,6 +14,25 @@ pub trait Intersect<S> {
     fn intersects(&self, other: &S) -> bool;
 }
 
+/// How two touching shapes meet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    /// Unit vector pointing from the other shape towards this one.
+    pub normal: glm::DVec2,
+    /// How far this shape has to move along `normal` to only just touch the other one.
+    pub depth: f64,
+}
+
+pub trait Collide<S> {
+    /// `None` unless the shapes touch or overlap.
+    fn contact(&self, other: &S) -> Option<Contact>;
+}
+
+/// Earliest fraction of a movement at which the moving shape touches `other`.
+pub trait Sweep<S> {
+    fn time_of_impact(&self, other: &S) -> Option<f64>;
+}
+
 pub trait Shape {
     fn get_center(&self) -> glm::DVec2;
     fn contains(&self, point: &glm::DVec2) -> bool;
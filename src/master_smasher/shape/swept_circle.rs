@@ -0,0 +1,237 @@
+use super::{Circle, Intersect, Rectangle, Sweep};
+
+use glm;
+
+#[derive(Clone, Copy)]
+pub struct SweptCircle {
+    pub circle: Circle,
+    pub displacement: glm::DVec2,
+}
+
+impl SweptCircle {
+    pub fn at(&self, time: f64) -> glm::DVec2 {
+        self.circle.center + self.displacement * time
+    }
+}
+
+impl Sweep<Circle> for SweptCircle {
+    fn time_of_impact(&self, other: &Circle) -> Option<f64> {
+        let radii = self.circle.radius + other.radius;
+        let offset = self.circle.center - other.center;
+        let c = glm::dot(offset, offset) - radii * radii;
+        if c < 0_f64 {
+            return Some(0_f64);
+        }
+
+        let a = glm::dot(self.displacement, self.displacement);
+        let b = 2_f64 * glm::dot(offset, self.displacement);
+        let discriminant = b * b - 4_f64 * a * c;
+        if a <= 0_f64 || discriminant < 0_f64 {
+            return None;
+        }
+
+        let time = (-b - discriminant.sqrt()) / (2_f64 * a);
+        if time >= 0_f64 && time <= 1_f64 {
+            Some(time)
+        } else {
+            None
+        }
+    }
+}
+
+impl Sweep<Rectangle> for SweptCircle {
+    fn time_of_impact(&self, other: &Rectangle) -> Option<f64> {
+        if self.circle.intersects(other) {
+            return Some(0_f64);
+        }
+
+        let half = other.dims / 2_f64;
+        let radius = self.circle.radius;
+        let start = self.circle.center;
+        let mut impacts = vec![];
+
+        // the flat sides of the rectangle grown by the radius
+        if self.displacement.x != 0_f64 {
+            let side = if self.displacement.x > 0_f64 {
+                other.center.x - half.x - radius
+            } else {
+                other.center.x + half.x + radius
+            };
+            let time = (side - start.x) / self.displacement.x;
+            if (self.at(time).y - other.center.y).abs() <= half.y {
+                impacts.push(time);
+            }
+        }
+        if self.displacement.y != 0_f64 {
+            let side = if self.displacement.y > 0_f64 {
+                other.center.y - half.y - radius
+            } else {
+                other.center.y + half.y + radius
+            };
+            let time = (side - start.y) / self.displacement.y;
+            if (self.at(time).x - other.center.x).abs() <= half.x {
+                impacts.push(time);
+            }
+        }
+
+        // the rounded corners
+        let corners = [glm::dvec2(-half.x, -half.y),
+                       glm::dvec2(half.x, -half.y),
+                       glm::dvec2(-half.x, half.y),
+                       half];
+        for offset in corners.iter() {
+            let corner = Circle {
+                center: other.center + *offset,
+                radius: 0_f64,
+            };
+            impacts.extend(self.time_of_impact(&corner));
+        }
+
+        impacts.into_iter()
+            .filter(|&t| t >= 0_f64 && t <= 1_f64)
+            .fold(None, |earliest: Option<f64>, t| Some(earliest.map_or(t, |e| e.min(t))))
+    }
+}
+
+impl<S> Intersect<S> for SweptCircle
+    where SweptCircle: Sweep<S>
+{
+    fn intersects(&self, other: &S) -> bool {
+        self.time_of_impact(other).is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn circle_sweep_miss() {
+        let swept = SweptCircle {
+            circle: Circle {
+                radius: 1_f64,
+                center: glm::dvec2(0_f64, 0_f64),
+            },
+            displacement: glm::dvec2(10_f64, 0_f64),
+        };
+
+        let circle = Circle {
+            radius: 1_f64,
+            center: glm::dvec2(5_f64, 3_f64),
+        };
+
+        assert_eq!(swept.time_of_impact(&circle), None);
+    }
+
+    #[test]
+    fn circle_sweep_tunnel() {
+        let swept = SweptCircle {
+            circle: Circle {
+                radius: 1_f64,
+                center: glm::dvec2(0_f64, 0_f64),
+            },
+            displacement: glm::dvec2(20_f64, 0_f64),
+        };
+
+        let circle = Circle {
+            radius: 1_f64,
+            center: glm::dvec2(10_f64, 0_f64),
+        };
+
+        assert!(!swept.circle.intersects(&circle));
+        assert_eq!(swept.time_of_impact(&circle), Some(0.4_f64));
+    }
+
+    #[test]
+    fn circle_sweep_already_intersecting() {
+        let swept = SweptCircle {
+            circle: Circle {
+                radius: 2_f64,
+                center: glm::dvec2(0_f64, 0_f64),
+            },
+            displacement: glm::dvec2(-5_f64, 0_f64),
+        };
+
+        let circle = Circle {
+            radius: 1_f64,
+            center: glm::dvec2(2_f64, 0_f64),
+        };
+
+        assert_eq!(swept.time_of_impact(&circle), Some(0_f64));
+    }
+
+    #[test]
+    fn circle_sweep_too_short() {
+        let swept = SweptCircle {
+            circle: Circle {
+                radius: 1_f64,
+                center: glm::dvec2(0_f64, 0_f64),
+            },
+            displacement: glm::dvec2(5_f64, 0_f64),
+        };
+
+        let circle = Circle {
+            radius: 1_f64,
+            center: glm::dvec2(10_f64, 0_f64),
+        };
+
+        assert_eq!(swept.time_of_impact(&circle), None);
+    }
+
+    #[test]
+    fn rectangle_sweep_side() {
+        let swept = SweptCircle {
+            circle: Circle {
+                radius: 1_f64,
+                center: glm::dvec2(0_f64, 0_f64),
+            },
+            displacement: glm::dvec2(0_f64, 20_f64),
+        };
+
+        let rectangle = Rectangle {
+            dims: glm::dvec2(4_f64, 2_f64),
+            center: glm::dvec2(1_f64, 10_f64),
+        };
+
+        assert!(!swept.circle.intersects(&rectangle));
+        assert_eq!(swept.time_of_impact(&rectangle), Some(0.4_f64));
+    }
+
+    #[test]
+    fn rectangle_sweep_corner() {
+        let swept = SweptCircle {
+            circle: Circle {
+                radius: 1_f64,
+                center: glm::dvec2(0_f64, 0_f64),
+            },
+            displacement: glm::dvec2(20_f64, 0_f64),
+        };
+
+        let rectangle = Rectangle {
+            dims: glm::dvec2(2_f64, 2_f64),
+            center: glm::dvec2(10_f64, 1.5_f64),
+        };
+
+        let time = swept.time_of_impact(&rectangle).unwrap();
+        assert!(time > 0.4_f64 && time < 0.41_f64);
+    }
+
+    #[test]
+    fn rectangle_sweep_miss() {
+        let swept = SweptCircle {
+            circle: Circle {
+                radius: 1_f64,
+                center: glm::dvec2(0_f64, 0_f64),
+            },
+            displacement: glm::dvec2(20_f64, 0_f64),
+        };
+
+        let rectangle = Rectangle {
+            dims: glm::dvec2(2_f64, 2_f64),
+            center: glm::dvec2(10_f64, 3_f64),
+        };
+
+        assert_eq!(swept.time_of_impact(&rectangle), None);
+        assert!(!swept.intersects(&rectangle));
+    }
+}
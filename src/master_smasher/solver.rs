@@ -0,0 +1,214 @@
+use master_smasher::{LevelAssets, LevelData, Simulation};
+use master_smasher::level::Outcome;
+use errors::*;
+
+use glm;
+
+use std::collections::HashMap;
+
+/// Ticks a shot may fly before the solver gives up on it.
+const MAX_FLIGHT_TICKS: u32 = 60 * 10;
+const COARSE_STEP: i32 = 40;
+/// How far past the window's edges aim points are sampled, as a fraction of its size. Aiming
+/// further out launches faster.
+const AIM_MARGIN: f64 = 0.5;
+const REFINE_STEPS: [i32; 3] = [13, 4, 1];
+
+/// Aim points that clear a level, in the order to shoot them.
+#[derive(Debug, PartialEq)]
+pub struct Solution {
+    pub targets: Vec<glm::IVec2>,
+}
+
+/// Searches launch targets for the fewest shots that destroy every enemy.
+///
+/// Only levels whose planets stay put are solved, and enemies don't deflect the meteor, so
+/// every shot flies the same no matter what came before it, at most going on past a target an
+/// earlier shot destroyed. That lets the solver find what each aim point destroys on its own and
+/// then pick the smallest set of shots that covers all the enemies. Levels it can't treat that
+/// way are refused with `ErrorKind::Unsupported`.
+pub struct Solver {
+    data: LevelData,
+    window_size: glm::UVec2,
+    assets: LevelAssets,
+    enemies: Vec<glm::DVec2>,
+}
+
+impl Solver {
+    pub fn new(data: LevelData, window_size: glm::UVec2, assets: LevelAssets) -> Self {
+        let enemies = Simulation::new(data.clone(), window_size, assets.clone()).enemy_centers();
+        Solver {
+            data: data,
+            window_size: window_size,
+            assets: assets,
+            enemies: enemies,
+        }
+    }
+
+    /// `None` if no set of shots clears the level.
+    pub fn solve(&self) -> Result<Option<Solution>> {
+        self.check_supported()?;
+
+        let mut shots: Vec<(u64, glm::IVec2)> = self.shots().into_iter().collect();
+        // best shots first so the search finds them early; sorted for a stable answer
+        shots.sort_by_key(|&(hits, target)| (!hits.count_ones(), hits, target.x, target.y));
+
+        let all = (0..self.enemies.len()).fold(0, |mask, i| mask | 1 << i);
+        let fewest = (0..self.enemies.len() + 1).filter_map(|n| cover(&shots, all, n)).next();
+        let solution = match fewest {
+            Some(targets) => Solution { targets: targets },
+            None => return Ok(None),
+        };
+        if self.verify(&solution) {
+            Ok(Some(solution))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn check_supported(&self) -> Result<()> {
+        let data = &self.data;
+        let feature = if data.enemies.len() > 64 {
+            "more than 64 enemies"
+        } else if data.planets.iter().any(|p| p.motion.is_some()) {
+            // when a shot is fired would matter as much as where it is aimed
+            "moving planets"
+        } else if !data.stations.is_empty() {
+            // and so would which shot it is
+            "launch stations"
+        } else if !data.meteors.is_empty() {
+            "meteor types"
+        } else {
+            return Ok(());
+        };
+        Err(ErrorKind::Unsupported(feature.to_string()).into())
+    }
+
+    /// Every distinct set of enemies a single shot destroys, with an aim point for each. A
+    /// coarse grid over the window and past its edges is refined around every aim point that
+    /// hit anything.
+    fn shots(&self) -> HashMap<u64, glm::IVec2> {
+        let size = glm::to_ivec2(self.window_size);
+        let margin = glm::to_ivec2(glm::to_dvec2(self.window_size) * AIM_MARGIN) / COARSE_STEP;
+        let cells = size / COARSE_STEP;
+
+        let mut found = HashMap::new();
+        let mut seeds = vec![];
+        for y in -margin.y..cells.y + margin.y {
+            for x in -margin.x..cells.x + margin.x {
+                let target = glm::ivec2(x, y) * COARSE_STEP + COARSE_STEP / 2;
+                let hits = self.hits(target);
+                if hits != 0 {
+                    found.entry(hits).or_insert(target);
+                    seeds.push(target);
+                }
+            }
+        }
+
+        for &step in &REFINE_STEPS {
+            for seed in &seeds {
+                for dy in -1..2 {
+                    for dx in -1..2 {
+                        let target = *seed + glm::ivec2(dx, dy) * step;
+                        let hits = self.hits(target);
+                        if hits != 0 {
+                            found.entry(hits).or_insert(target);
+                        }
+                    }
+                }
+            }
+            seeds = found.values().cloned().collect();
+        }
+        found
+    }
+
+    /// The enemies a single shot at `target` destroys, one bit per enemy.
+    fn hits(&self, target: glm::IVec2) -> u64 {
+        let mut simulation = self.simulation();
+        simulation.shoot(target, MAX_FLIGHT_TICKS);
+        let left = simulation.enemy_centers();
+        self.enemies
+            .iter()
+            .enumerate()
+            .filter(|&(_, e)| !left.contains(e))
+            .fold(0, |mask, (i, _)| mask | 1 << i)
+    }
+
+    /// Plays the whole solution in one go to make sure it really clears the level.
+    fn verify(&self, solution: &Solution) -> bool {
+        let mut simulation = self.simulation();
+        for target in &solution.targets {
+            simulation.shoot(*target, MAX_FLIGHT_TICKS);
+        }
+        simulation.outcome() == Some(Outcome::CLEARED)
+    }
+
+    fn simulation(&self) -> Simulation {
+        Simulation::new(self.data.clone(), self.window_size, self.assets.clone())
+    }
+}
+
+/// At most `shots` aim points whose hits add up to `missing`. Whichever shot ends up taking
+/// out the lowest missing enemy has to be among them, so only those are tried.
+fn cover(candidates: &[(u64, glm::IVec2)], missing: u64, shots: usize) -> Option<Vec<glm::IVec2>> {
+    if missing == 0 {
+        return Some(vec![]);
+    }
+    if shots == 0 {
+        return None;
+    }
+
+    let enemy = missing & missing.wrapping_neg();
+    candidates.iter()
+        .filter(|&&(hits, _)| hits & enemy != 0)
+        .filter_map(|&(hits, target)| {
+                        let rest = cover(candidates, missing & !hits, shots - 1);
+                        rest.map(|mut rest| {
+                                     rest.insert(0, target);
+                                     rest
+                                 })
+                    })
+        .next()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::HeadlessLoader;
+    use serde_yaml;
+
+    fn solver(data: LevelData) -> Solver {
+        let assets = LevelAssets::new(&HeadlessLoader::default()).unwrap();
+        Solver::new(data, glm::uvec2(1280, 720), assets)
+    }
+
+    #[test]
+    fn covers_with_fewest_shots() {
+        let a = glm::ivec2(1, 1);
+        let b = glm::ivec2(2, 2);
+        let c = glm::ivec2(3, 3);
+        let candidates = [(0b001, a), (0b010, b), (0b110, c)];
+        assert_eq!(cover(&candidates, 0b111, 1), None);
+        assert_eq!(cover(&candidates, 0b111, 2), Some(vec![a, c]));
+    }
+
+    #[test]
+    fn solves_first_level_with_a_shot_per_enemy() {
+        let data = LevelData::load("levels/level_1.lvl").unwrap();
+        let solution = solver(data).solve().unwrap().unwrap();
+        assert_eq!(solution.targets.len(), 2);
+    }
+
+    #[test]
+    fn refuses_moving_planets() {
+        let mut data = LevelData::load("levels/level_1.lvl").unwrap();
+        data.planets[0].motion = serde_yaml::from_str("{ORBIT: {x: 400, y: 300, period: 600}}")
+            .unwrap();
+        match solver(data).solve() {
+            Err(Error(ErrorKind::Unsupported(ref feature), _)) => {
+                assert_eq!(feature, "moving planets")
+            }
+            _ => panic!("moving planets were not refused as unsupported"),
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use master_smasher::drawable::{digits, outline};
+use master_smasher::level::{self, Body, Diagnostic, Edges, Gravity, Level, LevelData,
+                            ObjectData, Overlay, Physics, PlanetData, PlanetKind, Severity};
+use master_smasher::shape::Circle;
+use super::{Context, GameState, Gameplay, State, Transition};
+use errors::*;
+
+use glm;
+use moho::input_manager::{EventPump, InputManager};
+use moho::resource_manager::{Renderer, ResourceLoader, TextureId};
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::rect;
+
+use std::path::{Path, PathBuf};
+
+const RING_STEP: f64 = 10.;
+const STRENGTH_STEP: f64 = 50.;
+const RESTITUTION: f64 = 0.8;
+
+/// What a left click on empty space places.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tool {
+    RED,
+    BLUE,
+    WHITE,
+    PURPLE,
+    DEAD,
+    STAR,
+    ENEMY,
+}
+
+impl Tool {
+    fn from_input<P: EventPump>(input: &InputManager<P>) -> Option<Tool> {
+        let tools = [(Keycode::Num1, Tool::RED),
+                     (Keycode::Num2, Tool::BLUE),
+                     (Keycode::Num3, Tool::WHITE),
+                     (Keycode::Num4, Tool::DEAD),
+                     (Keycode::Num5, Tool::STAR),
+                     (Keycode::Num6, Tool::ENEMY),
+                     (Keycode::Num7, Tool::PURPLE)];
+        tools.iter().find(|&&(k, _)| input.did_press_key(k)).map(|&(_, t)| t)
+    }
+}
+
+/// What came of the last save, shown until the level is changed again.
+enum Saved {
+    OK(Vec<Diagnostic>),
+    FAILED,
+}
+
+/// Lays out a level with the mouse.
+///
+/// 1-4 pick a red, blue, white or dead planet, 5/6 a star or enemy and 7 a repulsor; left
+/// click places it or drags whatever is under the mouse, right click deletes. Up/Down grow or
+/// shrink the selected planet's ring, Left/Right change its strength and B makes it bouncy or
+/// not. Return playtests and S saves, then circles whatever is still wrong with the level,
+/// crossing out the errors, and counts the errors and warnings in the top right corner. A
+/// cross alone in that corner means the save failed.
+pub struct Editor {
+    path: PathBuf,
+    data: LevelData,
+    level: Level,
+    tool: Tool,
+    dragging: Option<Body>,
+    selected: Option<usize>,
+    saved: Option<Saved>,
+    window_size: glm::UVec2,
+    background: TextureId,
+}
+
+impl Editor {
+    /// Edits the level at `path`, starting from an empty one if there is no such file yet.
+    pub fn load<P, L>(path: P, context: &Context, resource_loader: &L) -> Result<Self>
+        where P: AsRef<Path>,
+              L: ResourceLoader
+    {
+        let path = path.as_ref();
+        let data = if path.exists() {
+            LevelData::load(path)?
+        } else {
+            let center = glm::to_ivec2(context.window_size) / 2;
+            LevelData {
+                meteor: ObjectData {
+                    x: 100,
+                    y: center.y,
+                },
+                stars: vec![],
+                enemies: vec![],
+                stations: vec![],
+                meteors: vec![],
+                planets: vec![],
+                portals: vec![],
+                edges: Edges::default(),
+                physics: Physics::default(),
+                preview: None,
+                shots: None,
+                fuel: None,
+            }
+        };
+
+        let level = Level::new(data.clone(), context.window_size, context.level_assets.clone());
+        let background = resource_loader.load_texture("resources/background_game.png")?;
+        Ok(Editor {
+               path: path.to_path_buf(),
+               data: data,
+               level: level,
+               tool: Tool::RED,
+               dragging: None,
+               selected: None,
+               saved: None,
+               window_size: context.window_size,
+               background: background.id,
+           })
+    }
+
+    fn place(&mut self, point: glm::IVec2) {
+        let object = ObjectData {
+            x: point.x,
+            y: point.y,
+        };
+        let kind = match self.tool {
+            Tool::STAR => return self.data.stars.push(object),
+            Tool::ENEMY => return self.data.enemies.push(object),
+            Tool::RED => {
+                PlanetKind::RED {
+                    ring: 200.,
+                    strength: 500.,
+                }
+            }
+            Tool::BLUE => {
+                PlanetKind::BLUE {
+                    ring: 200.,
+                    strength: 500.,
+                }
+            }
+            Tool::WHITE => {
+                PlanetKind::WHITE {
+                    ring: 200.,
+                    strength: 500.,
+                }
+            }
+            Tool::PURPLE => {
+                PlanetKind::PURPLE {
+                    ring: 200.,
+                    strength: 500.,
+                }
+            }
+            Tool::DEAD => PlanetKind::DEAD,
+        };
+        self.data.planets.push(PlanetData {
+                                   x: point.x,
+                                   y: point.y,
+                                   kind: kind,
+                                   gravity: Gravity::default(),
+                                   bouncy: None,
+                                   motion: None,
+                               });
+        self.selected = Some(self.data.planets.len() - 1);
+    }
+
+    fn move_body(&mut self, body: Body, point: glm::IVec2) {
+        let object = match body {
+            Body::METEOR => &mut self.data.meteor,
+            Body::STAR(i) => &mut self.data.stars[i],
+            Body::ENEMY(i) => &mut self.data.enemies[i],
+            Body::PLANET(i) => {
+                let planet = &mut self.data.planets[i];
+                planet.x = point.x;
+                planet.y = point.y;
+                return;
+            }
+        };
+        object.x = point.x;
+        object.y = point.y;
+    }
+
+    /// There is always a meteor, so it can only be moved.
+    fn delete(&mut self, body: Body) {
+        match body {
+            Body::METEOR => {}
+            Body::PLANET(i) => {
+                self.data.planets.remove(i);
+                self.selected = None;
+            }
+            Body::STAR(i) => {
+                self.data.stars.remove(i);
+            }
+            Body::ENEMY(i) => {
+                self.data.enemies.remove(i);
+            }
+        }
+    }
+
+    fn adjust_selected(&mut self, ring_delta: f64, strength_delta: f64) {
+        let planet = match self.selected.and_then(|i| self.data.planets.get_mut(i)) {
+            Some(p) => p,
+            None => return,
+        };
+        match planet.kind {
+            PlanetKind::RED { ref mut ring, ref mut strength } |
+            PlanetKind::BLUE { ref mut ring, ref mut strength } |
+            PlanetKind::WHITE { ref mut ring, ref mut strength } |
+            PlanetKind::PURPLE { ref mut ring, ref mut strength } => {
+                *ring = (*ring + ring_delta).max(RING_STEP);
+                *strength = (*strength + strength_delta).max(0.);
+            }
+            PlanetKind::DEAD => {}
+        }
+    }
+
+    /// Applies one update's worth of edits, returning whether the level changed.
+    fn edit<P: EventPump>(&mut self, input: &InputManager<P>) -> bool {
+        let mouse = input.mouse_coords();
+        if let Some(tool) = Tool::from_input(input) {
+            self.tool = tool;
+        }
+
+        if input.did_click_mouse(MouseButton::Left) {
+            match self.level.body_at(mouse) {
+                Some(body) => {
+                    if let Body::PLANET(i) = body {
+                        self.selected = Some(i);
+                    }
+                    self.dragging = Some(body);
+                }
+                None => {
+                    self.place(mouse);
+                    return true;
+                }
+            }
+        }
+        if input.did_release_mouse(MouseButton::Left) {
+            self.dragging = None;
+        }
+        if let Some(body) = self.dragging {
+            self.move_body(body, mouse);
+            return true;
+        }
+
+        if input.did_click_mouse(MouseButton::Right) {
+            if let Some(body) = self.level.body_at(mouse) {
+                self.delete(body);
+                return true;
+            }
+        }
+
+        let adjustments = [(Keycode::Up, RING_STEP, 0.),
+                           (Keycode::Down, -RING_STEP, 0.),
+                           (Keycode::Right, 0., STRENGTH_STEP),
+                           (Keycode::Left, 0., -STRENGTH_STEP)];
+        let mut changed = false;
+        if input.did_press_key(Keycode::B) {
+            if let Some(planet) = self.selected.and_then(|i| self.data.planets.get_mut(i)) {
+                planet.bouncy = match planet.bouncy {
+                    Some(_) => None,
+                    None => Some(RESTITUTION),
+                };
+                changed = true;
+            }
+        }
+        for &(key, ring, strength) in &adjustments {
+            if input.did_press_key(key) {
+                self.adjust_selected(ring, strength);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn draw_saved<R: Renderer>(&self, renderer: &mut R) -> Result<()> {
+        const MARK: f64 = 40.;
+        const KEY: f64 = 8.;
+        const DIGIT_WIDTH: u32 = 10;
+        const SLOT: i32 = 60;
+        const MARGIN: i32 = 10;
+
+        let diagnostics = match self.saved {
+            Some(Saved::OK(ref d)) => d,
+            Some(Saved::FAILED) => {
+                let corner = glm::dvec2(self.window_size.x as f64 - MARK, MARK);
+                let mut rects = vec![];
+                cross(corner, MARK / 2., &mut rects);
+                return renderer.fill_rects(&rects).map_err(Into::into);
+            }
+            None => return Ok(()),
+        };
+
+        let mut rects = vec![];
+        for d in diagnostics {
+            if let Some(position) = d.position {
+                mark(d.severity, glm::to_dvec2(position), MARK, &mut rects);
+            }
+        }
+        let severities = [Severity::ERROR, Severity::WARNING];
+        for (i, &severity) in severities.iter().enumerate() {
+            let count = diagnostics.iter().filter(|d| d.severity == severity).count();
+            let x = self.window_size.x as i32 - (2 - i as i32) * SLOT;
+            let key = glm::dvec2((x + MARGIN) as f64, (MARGIN + DIGIT_WIDTH as i32) as f64);
+            mark(severity, key, KEY, &mut rects);
+            let position = glm::ivec2(x + 2 * MARGIN + KEY as i32, MARGIN);
+            digits::number(count as u32, position, DIGIT_WIDTH, &mut rects);
+        }
+        renderer.fill_rects(&rects).map_err(Into::into)
+    }
+}
+
+/// A circle around what a diagnostic is about, crossed out for errors.
+fn mark(severity: Severity, center: glm::DVec2, radius: f64, rects: &mut Vec<rect::Rect>) {
+    outline::circle(&Circle {
+                         center: center,
+                         radius: radius,
+                     },
+                    rects);
+    if severity == Severity::ERROR {
+        cross(center, radius, rects);
+    }
+}
+
+fn cross(center: glm::DVec2, radius: f64, rects: &mut Vec<rect::Rect>) {
+    let reach = radius / 2_f64.sqrt();
+    let diagonals = [glm::dvec2(reach, reach), glm::dvec2(reach, -reach)];
+    for &d in &diagonals {
+        outline::segment(center - d, center + d, rects);
+    }
+}
+
+impl GameState for Editor {
+    fn update<P, L>(&mut self,
+                    input: &InputManager<P>,
+                    context: &mut Context,
+                    resource_loader: &L)
+                    -> Result<Option<Transition>>
+        where P: EventPump,
+              L: ResourceLoader
+    {
+        if input.did_press_key(Keycode::Return) {
+            let gameplay = Gameplay::test(self.data.clone(), context, resource_loader)?;
+            return Ok(Some(Transition::PUSH(State::PLAYING(gameplay))));
+        }
+        if input.did_press_key(Keycode::S) {
+            let saved = match self.data.save(&self.path) {
+                Ok(_) => {
+                    let assets = &context.level_assets;
+                    Saved::OK(level::validate(&self.data, context.window_size, assets))
+                }
+                Err(e) => {
+                    context.warnings
+                        .push(format!("could not save {}: {}", self.path.display(), e));
+                    Saved::FAILED
+                }
+            };
+            self.saved = Some(saved);
+        }
+
+        if self.edit(input) {
+            self.saved = None;
+            let assets = context.level_assets.clone();
+            self.level = Level::new(self.data.clone(), context.window_size, assets);
+        }
+        Ok(None)
+    }
+
+    // never animated, so rings stay drawn at their full radius
+    fn draw<R: Renderer>(&self, interpolation: f64, renderer: &mut R) -> Result<()> {
+        renderer.show(&self.background)?;
+        self.level.draw(interpolation, renderer)?;
+        self.draw_saved(renderer)
+    }
+
+    fn draw_overlay<R: Renderer>(&self, overlay: Overlay, renderer: &mut R) -> Result<()> {
+        self.level.draw_overlay(overlay, renderer)
+    }
+}
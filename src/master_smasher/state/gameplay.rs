@@ -0,0 +1,181 @@
+use master_smasher::level::{self, Level, LevelData, Outcome, Overlay, PlayerInput};
+use master_smasher::replay::Replay;
+use super::{Context, GameState, Pause, Results, State, Transition};
+use errors::*;
+
+use moho::input_manager::{EventPump, InputManager};
+use moho::resource_manager::{Renderer, ResourceLoader, TextureId};
+use sdl2::keyboard::Keycode;
+
+use std::path::Path;
+use std::time::Duration;
+
+enum Source {
+    /// The campaign level at `index` played live, recorded as it goes.
+    PLAYER { index: usize, replay: Replay },
+    /// A recording fed back one input per tick.
+    REPLAY {
+        inputs: Vec<PlayerInput>,
+        tick: usize,
+    },
+    /// Live input on a level straight from the editor; Return goes back to it.
+    TEST,
+}
+
+pub struct Gameplay {
+    source: Source,
+    level: Level,
+    background: TextureId,
+}
+
+impl Gameplay {
+    /// Starts the campaign level at `index`.
+    pub fn load<L: ResourceLoader>(index: usize,
+                                   context: &mut Context,
+                                   resource_loader: &L)
+                                   -> Result<Self> {
+        let path = context.campaign
+            .level(index)
+            .ok_or("no such level in the campaign")?
+            .to_path_buf();
+        let source = Source::PLAYER {
+            index: index,
+            replay: Replay::new(&path),
+        };
+        let data = Gameplay::load_valid(&path, context)?;
+        Gameplay::start(data, source, context, resource_loader)
+    }
+
+    /// Plays back a recording instead of listening to the player.
+    pub fn replay<L: ResourceLoader>(replay: &Replay,
+                                     context: &mut Context,
+                                     resource_loader: &L)
+                                     -> Result<Self> {
+        let source = Source::REPLAY {
+            inputs: replay.inputs(),
+            tick: 0,
+        };
+        let data = Gameplay::load_valid(replay.level(), context)?;
+        Gameplay::start(data, source, context, resource_loader)
+    }
+
+    /// Passes the level's warnings on to be reported; they don't stop it from being played.
+    fn load_valid<P: AsRef<Path>>(path: P, context: &mut Context) -> Result<LevelData> {
+        let (data, warnings) = level::load_valid(path, context.window_size, &context.level_assets)?;
+        context.warnings.extend(warnings.iter().map(|w| w.to_string()));
+        Ok(data)
+    }
+
+    /// Playtests a level being edited.
+    pub fn test<L: ResourceLoader>(data: LevelData,
+                                   context: &Context,
+                                   resource_loader: &L)
+                                   -> Result<Self> {
+        Gameplay::start(data, Source::TEST, context, resource_loader)
+    }
+
+    fn start<L: ResourceLoader>(data: LevelData,
+                                source: Source,
+                                context: &Context,
+                                resource_loader: &L)
+                                -> Result<Self> {
+        let assets = context.level_assets.clone();
+        let level = Level::new(data, context.window_size, assets);
+        let background = resource_loader.load_texture("resources/background_game.png")?;
+        Ok(Gameplay {
+               source: source,
+               level: level,
+               background: background.id,
+           })
+    }
+
+    fn finish(&self, outcome: Outcome, context: &mut Context) -> Result<Option<Transition>> {
+        let (index, replay) = match self.source {
+            Source::PLAYER { index, ref replay } => (index, replay),
+            Source::REPLAY { .. } |
+            Source::TEST => return Ok(None),
+        };
+
+        let score = self.level.score();
+        if outcome == Outcome::CLEARED {
+            let path = context.campaign.level(index).ok_or("no such level in the campaign")?;
+            context.progress.record_clear(path, score);
+            if let Some(ref save_path) = context.save_path {
+                if let Err(e) = context.progress.save(save_path) {
+                    let warning = format!("could not save progress to {}: {}",
+                                          save_path.display(),
+                                          e);
+                    context.warnings.push(warning);
+                }
+            }
+        }
+        if let Some(ref dir) = context.replay_dir {
+            let stem = replay.level().file_stem().ok_or("level path has no file name")?;
+            let path = dir.join(stem).with_extension("yaml");
+            if let Err(e) = replay.save(&path) {
+                let warning = format!("could not save replay to {}: {}", path.display(), e);
+                context.warnings.push(warning);
+            }
+        }
+
+        let assets = context.results_assets.clone();
+        let results = Results::new(index, outcome, score, assets, context.window_size);
+        Ok(Some(Transition::PUSH(State::RESULTS(results))))
+    }
+}
+
+impl GameState for Gameplay {
+    fn update<P, L>(&mut self,
+                    input: &InputManager<P>,
+                    context: &mut Context,
+                    resource_loader: &L)
+                    -> Result<Option<Transition>>
+        where P: EventPump,
+              L: ResourceLoader
+    {
+        if input.did_press_key(Keycode::P) {
+            let pause = Pause::new(resource_loader, context.window_size)?;
+            return Ok(Some(Transition::PUSH(State::PAUSED(pause))));
+        }
+
+        let player_input = match self.source {
+            Source::PLAYER { ref mut replay, .. } => {
+                let player_input = PlayerInput::from_input(input);
+                replay.record(&player_input);
+                player_input
+            }
+            Source::TEST if input.did_press_key(Keycode::Return) => {
+                return Ok(Some(Transition::POP(1)));
+            }
+            Source::TEST => PlayerInput::from_input(input),
+            Source::REPLAY { ref inputs, ref mut tick } => {
+                match inputs.get(*tick) {
+                    Some(i) => {
+                        *tick += 1;
+                        *i
+                    }
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        self.level.update(&player_input);
+        match self.level.outcome() {
+            Some(outcome) => self.finish(outcome, context),
+            None => Ok(None),
+        }
+    }
+
+    fn animate(&mut self, delta: Duration) {
+        self.level.animate(delta);
+    }
+
+    fn draw<R: Renderer>(&self, interpolation: f64, renderer: &mut R) -> Result<()> {
+        renderer.show(&self.background)?;
+        self.level.draw(interpolation, renderer)
+    }
+
+    fn draw_overlay<R: Renderer>(&self, overlay: Overlay, renderer: &mut R) -> Result<()> {
+        self.level.draw_overlay(overlay, renderer)
+    }
+}
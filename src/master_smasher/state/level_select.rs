@@ -0,0 +1,129 @@
+use master_smasher::drawable::{Button, StarRating, StarRatingAssets, TryIterator};
+use master_smasher::drawable::layout;
+use master_smasher::level::LevelData;
+use super::{Context, GameState, Gameplay, State, Transition};
+use errors::*;
+
+use glm;
+use moho::errors as moho_errors;
+use moho::input_manager::{EventPump, InputManager};
+use moho::resource_manager::{Renderer, ResourceLoader, Scene, TextureId};
+use sdl2::mouse::MouseButton;
+
+struct LevelTile {
+    planet: Button,
+    stars: StarRating,
+    unlocked: bool,
+}
+
+impl Scene for LevelTile {
+    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
+        renderer.show(&self.planet)?;
+        renderer.show(&self.stars)
+    }
+}
+
+/// Grid with a planet per campaign level: lively for unlocked levels, dead for locked ones,
+/// with the best stars earned underneath.
+pub struct LevelSelect {
+    background: TextureId,
+    window: glm::IVec4,
+    tiles: Vec<LevelTile>,
+}
+
+impl LevelSelect {
+    pub fn new<L: ResourceLoader>(context: &Context, resource_loader: &L) -> Result<Self> {
+        const COLUMNS: usize = 5;
+        const SPACING: i32 = 200;
+
+        let background = resource_loader.load_texture("resources/background_menu.png")?;
+        let cleared = resource_loader.load_texture("resources/blue_planet.png")?;
+        let open = resource_loader.load_texture("resources/white_planet.png")?;
+        let locked = resource_loader.load_texture("resources/dead_planet.png")?;
+        let star_assets = StarRatingAssets::new(resource_loader)?;
+
+        let campaign = &context.campaign;
+        let window_size = context.window_size;
+        let window_center = glm::to_ivec2(window_size) / 2;
+        let columns = if campaign.len() < COLUMNS {
+            campaign.len()
+        } else {
+            COLUMNS
+        };
+        let rows = (campaign.len() + COLUMNS - 1) / COLUMNS;
+        let origin = window_center -
+                     glm::ivec2(columns as i32 - 1, rows as i32 - 1) * SPACING / 2;
+
+        let mut tiles = Vec::with_capacity(campaign.len());
+        for i in 0..campaign.len() {
+            let path = campaign.level(i).ok_or("no such level in the campaign")?;
+            let data = LevelData::load(path)?;
+            let record = context.progress.get(path);
+            let unlocked = context.progress.is_unlocked(campaign, i);
+            let texture = if record.cleared {
+                cleared
+            } else if unlocked {
+                open
+            } else {
+                locked
+            };
+
+            let center = origin + glm::ivec2((i % COLUMNS) as i32, (i / COLUMNS) as i32) * SPACING;
+            let stars = StarRating::new(record.stars,
+                                        data.stars.len() as u32,
+                                        0.5,
+                                        center + glm::ivec2(0, 80),
+                                        star_assets.clone());
+            tiles.push(LevelTile {
+                           planet: Button::new(texture, center),
+                           stars: stars,
+                           unlocked: unlocked,
+                       });
+        }
+
+        Ok(LevelSelect {
+               background: background.id,
+               window: layout::centered(glm::to_ivec2(window_size), window_center),
+               tiles: tiles,
+           })
+    }
+
+    /// The index of the unlocked level at `point`, if any.
+    pub fn choice(&self, point: glm::IVec2) -> Option<usize> {
+        self.tiles.iter().position(|t| t.unlocked && t.planet.contains(point))
+    }
+}
+
+impl GameState for LevelSelect {
+    fn update<P, L>(&mut self,
+                    input: &InputManager<P>,
+                    context: &mut Context,
+                    resource_loader: &L)
+                    -> Result<Option<Transition>>
+        where P: EventPump,
+              L: ResourceLoader
+    {
+        if !input.did_click_mouse(MouseButton::Left) {
+            return Ok(None);
+        }
+
+        match self.choice(input.mouse_coords()) {
+            Some(index) => {
+                let gameplay = Gameplay::load(index, context, resource_loader)?;
+                Ok(Some(Transition::REPLACE(1, State::PLAYING(gameplay))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn draw<R: Renderer>(&self, _: f64, renderer: &mut R) -> Result<()> {
+        renderer.show(self).map_err(Into::into)
+    }
+}
+
+impl Scene for LevelSelect {
+    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
+        renderer.render(&self.background, self.window)?;
+        self.tiles.iter().try(|t| renderer.show(t))
+    }
+}
@@ -0,0 +1,94 @@
+use master_smasher::drawable::Button;
+use master_smasher::drawable::layout;
+use super::{Context, GameState, Gameplay, LevelSelect, State, Transition};
+use errors::*;
+
+use glm;
+use moho::errors as moho_errors;
+use moho::input_manager::{EventPump, InputManager};
+use moho::resource_manager::{Renderer, ResourceLoader, Scene, TextureId};
+use sdl2::mouse::MouseButton;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuChoice {
+    PLAY,
+    LEVELS,
+}
+
+pub struct Menu {
+    background: TextureId,
+    window: glm::IVec4,
+    title: Button,
+    new_game: Button,
+    level_select: Button,
+}
+
+impl Menu {
+    pub fn new<L>(resource_loader: &L, window_size: glm::UVec2) -> moho_errors::Result<Self>
+        where L: ResourceLoader
+    {
+        let background = resource_loader.load_texture("resources/background_menu.png")?;
+        let title = resource_loader.load_texture("resources/Title.png")?;
+        let new_game = resource_loader.load_texture("resources/NewGame.png")?;
+        let level_select = resource_loader.load_texture("resources/LevelSelect.png")?;
+
+        let center = glm::to_ivec2(window_size) / 2;
+        Ok(Menu {
+               background: background.id,
+               window: layout::centered(glm::to_ivec2(window_size), center),
+               title: Button::new(title, center - glm::ivec2(0, 150)),
+               new_game: Button::new(new_game, center + glm::ivec2(0, 50)),
+               level_select: Button::new(level_select, center + glm::ivec2(0, 110)),
+           })
+    }
+
+    pub fn choice(&self, point: glm::IVec2) -> Option<MenuChoice> {
+        if self.new_game.contains(point) {
+            Some(MenuChoice::PLAY)
+        } else if self.level_select.contains(point) {
+            Some(MenuChoice::LEVELS)
+        } else {
+            None
+        }
+    }
+}
+
+impl GameState for Menu {
+    fn update<P, L>(&mut self,
+                    input: &InputManager<P>,
+                    context: &mut Context,
+                    resource_loader: &L)
+                    -> Result<Option<Transition>>
+        where P: EventPump,
+              L: ResourceLoader
+    {
+        if !input.did_click_mouse(MouseButton::Left) {
+            return Ok(None);
+        }
+
+        match self.choice(input.mouse_coords()) {
+            Some(MenuChoice::PLAY) => {
+                let gameplay = Gameplay::load(0, context, resource_loader)?;
+                Ok(Some(Transition::PUSH(State::PLAYING(gameplay))))
+            }
+            Some(MenuChoice::LEVELS) => {
+                let select = LevelSelect::new(context, resource_loader)?;
+                Ok(Some(Transition::PUSH(State::LEVELS(select))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn draw<R: Renderer>(&self, _: f64, renderer: &mut R) -> Result<()> {
+        renderer.show(self).map_err(Into::into)
+    }
+}
+
+impl Scene for Menu {
+    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
+        renderer.render(&self.background, self.window)?;
+        renderer.show(&self.title)?;
+        renderer.show(&self.new_game)?;
+        renderer.show(&self.level_select)
+    }
+}
@@ -0,0 +1,258 @@
+mod editor;
+mod gameplay;
+mod level_select;
+mod menu;
+mod pause;
+mod results;
+
+pub use self::editor::Editor;
+pub use self::gameplay::Gameplay;
+pub use self::level_select::LevelSelect;
+pub use self::menu::Menu;
+pub use self::pause::Pause;
+pub use self::results::{Results, ResultsAssets};
+
+use master_smasher::campaign::Campaign;
+use master_smasher::level::{LevelAssets, Overlay};
+use master_smasher::progress::Progress;
+use errors::*;
+
+use glm;
+use moho::input_manager::{EventPump, InputManager};
+use moho::resource_manager::{Renderer, ResourceLoader};
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Everything the states share and outlives any one of them.
+pub struct Context {
+    pub campaign: Campaign,
+    pub progress: Progress,
+    pub save_path: Option<PathBuf>,
+    /// Where the last run of each level is recorded.
+    pub replay_dir: Option<PathBuf>,
+    pub level_assets: LevelAssets,
+    pub results_assets: ResultsAssets,
+    pub window_size: glm::UVec2,
+    /// Whatever went wrong without stopping the game, for `main` to report.
+    pub warnings: Vec<String>,
+}
+
+/// What a state asks of the stack after an update.
+pub enum Transition {
+    PUSH(State),
+    /// Pops the given number of states.
+    POP(usize),
+    /// Pops the given number of states and pushes a new one in their place.
+    REPLACE(usize, State),
+}
+
+/// A screen of the game. `update` runs at the fixed game speed while `animate` and `draw` run
+/// once per frame, just like the game loop itself.
+pub trait GameState {
+    fn update<P, L>(&mut self,
+                    input: &InputManager<P>,
+                    context: &mut Context,
+                    resource_loader: &L)
+                    -> Result<Option<Transition>>
+        where P: EventPump,
+              L: ResourceLoader;
+
+    fn animate(&mut self, _delta: Duration) {}
+
+    fn draw<R: Renderer>(&self, interpolation: f64, renderer: &mut R) -> Result<()>;
+
+    /// Drawn over `draw` while any debug layer is on.
+    fn draw_overlay<R: Renderer>(&self, _overlay: Overlay, _renderer: &mut R) -> Result<()> {
+        Ok(())
+    }
+
+    /// Overlays are drawn on top of the state below them instead of replacing it.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+pub enum State {
+    MENU(Menu),
+    LEVELS(LevelSelect),
+    PLAYING(Gameplay),
+    PAUSED(Pause),
+    RESULTS(Results),
+    EDITOR(Editor),
+}
+
+impl GameState for State {
+    fn update<P, L>(&mut self,
+                    input: &InputManager<P>,
+                    context: &mut Context,
+                    resource_loader: &L)
+                    -> Result<Option<Transition>>
+        where P: EventPump,
+              L: ResourceLoader
+    {
+        match *self {
+            State::MENU(ref mut s) => s.update(input, context, resource_loader),
+            State::LEVELS(ref mut s) => s.update(input, context, resource_loader),
+            State::PLAYING(ref mut s) => s.update(input, context, resource_loader),
+            State::PAUSED(ref mut s) => s.update(input, context, resource_loader),
+            State::RESULTS(ref mut s) => s.update(input, context, resource_loader),
+            State::EDITOR(ref mut s) => s.update(input, context, resource_loader),
+        }
+    }
+
+    fn animate(&mut self, delta: Duration) {
+        match *self {
+            State::MENU(ref mut s) => s.animate(delta),
+            State::LEVELS(ref mut s) => s.animate(delta),
+            State::PLAYING(ref mut s) => s.animate(delta),
+            State::PAUSED(ref mut s) => s.animate(delta),
+            State::RESULTS(ref mut s) => s.animate(delta),
+            State::EDITOR(ref mut s) => s.animate(delta),
+        }
+    }
+
+    fn draw<R: Renderer>(&self, interpolation: f64, renderer: &mut R) -> Result<()> {
+        match *self {
+            State::MENU(ref s) => s.draw(interpolation, renderer),
+            State::LEVELS(ref s) => s.draw(interpolation, renderer),
+            State::PLAYING(ref s) => s.draw(interpolation, renderer),
+            State::PAUSED(ref s) => s.draw(interpolation, renderer),
+            State::RESULTS(ref s) => s.draw(interpolation, renderer),
+            State::EDITOR(ref s) => s.draw(interpolation, renderer),
+        }
+    }
+
+    fn draw_overlay<R: Renderer>(&self, overlay: Overlay, renderer: &mut R) -> Result<()> {
+        match *self {
+            State::MENU(ref s) => s.draw_overlay(overlay, renderer),
+            State::LEVELS(ref s) => s.draw_overlay(overlay, renderer),
+            State::PLAYING(ref s) => s.draw_overlay(overlay, renderer),
+            State::PAUSED(ref s) => s.draw_overlay(overlay, renderer),
+            State::RESULTS(ref s) => s.draw_overlay(overlay, renderer),
+            State::EDITOR(ref s) => s.draw_overlay(overlay, renderer),
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        match *self {
+            State::MENU(ref s) => s.is_overlay(),
+            State::LEVELS(ref s) => s.is_overlay(),
+            State::PLAYING(ref s) => s.is_overlay(),
+            State::PAUSED(ref s) => s.is_overlay(),
+            State::RESULTS(ref s) => s.is_overlay(),
+            State::EDITOR(ref s) => s.is_overlay(),
+        }
+    }
+}
+
+/// Only the top state is updated and animated; it is drawn together with whatever it overlays.
+pub struct StateStack {
+    states: Vec<State>,
+}
+
+impl StateStack {
+    pub fn new(initial: State) -> Self {
+        StateStack { states: vec![initial] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn apply(&mut self, transition: Transition) {
+        match transition {
+            Transition::PUSH(state) => self.states.push(state),
+            Transition::POP(count) => self.pop(count),
+            Transition::REPLACE(count, state) => {
+                self.pop(count);
+                self.states.push(state);
+            }
+        }
+    }
+
+    fn pop(&mut self, count: usize) {
+        let len = self.states.len().saturating_sub(count);
+        self.states.truncate(len);
+    }
+
+    pub fn update<P, L>(&mut self,
+                        input: &InputManager<P>,
+                        context: &mut Context,
+                        resource_loader: &L)
+                        -> Result<()>
+        where P: EventPump,
+              L: ResourceLoader
+    {
+        let transition = match self.states.last_mut() {
+            Some(s) => s.update(input, context, resource_loader)?,
+            None => None,
+        };
+        if let Some(t) = transition {
+            self.apply(t);
+        }
+        Ok(())
+    }
+
+    pub fn animate(&mut self, delta: Duration) {
+        if let Some(s) = self.states.last_mut() {
+            s.animate(delta);
+        }
+    }
+
+    pub fn draw<R: Renderer>(&self, interpolation: f64, renderer: &mut R) -> Result<()> {
+        for state in self.visible() {
+            state.draw(interpolation, renderer)?;
+        }
+        Ok(())
+    }
+
+    pub fn draw_overlay<R: Renderer>(&self, overlay: Overlay, renderer: &mut R) -> Result<()> {
+        for state in self.visible() {
+            state.draw_overlay(overlay, renderer)?;
+        }
+        Ok(())
+    }
+
+    fn visible(&self) -> &[State] {
+        let base = self.states.iter().rposition(|s| !s.is_overlay()).unwrap_or(0);
+        &self.states[base..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use master_smasher::HeadlessLoader;
+
+    fn menu() -> State {
+        let loader = HeadlessLoader::default();
+        State::MENU(Menu::new(&loader, glm::uvec2(1280, 720)).unwrap())
+    }
+
+    fn pause() -> State {
+        let loader = HeadlessLoader::default();
+        State::PAUSED(Pause::new(&loader, glm::uvec2(1280, 720)).unwrap())
+    }
+
+    #[test]
+    fn push_and_pop() {
+        let mut stack = StateStack::new(menu());
+        stack.apply(Transition::PUSH(pause()));
+        assert_eq!(stack.states.len(), 2);
+        stack.apply(Transition::POP(1));
+        assert_eq!(stack.states.len(), 1);
+        stack.apply(Transition::POP(1));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn replace_pops_before_pushing() {
+        let mut stack = StateStack::new(menu());
+        stack.apply(Transition::PUSH(pause()));
+        stack.apply(Transition::PUSH(pause()));
+        stack.apply(Transition::REPLACE(2, menu()));
+        assert_eq!(stack.states.len(), 2);
+        assert!(!stack.states[1].is_overlay());
+    }
+}
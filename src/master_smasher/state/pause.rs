@@ -0,0 +1,48 @@
+use master_smasher::drawable::Button;
+use super::{Context, GameState, Transition};
+use errors::*;
+
+use glm;
+use moho::input_manager::{EventPump, InputManager};
+use moho::resource_manager::{Renderer, ResourceLoader};
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+
+/// Freezes the state below it until the player resumes with P or the button.
+pub struct Pause {
+    resume: Button,
+}
+
+impl Pause {
+    pub fn new<L: ResourceLoader>(resource_loader: &L, window_size: glm::UVec2) -> Result<Self> {
+        let resume = resource_loader.load_texture("resources/ResumeButton.png")?;
+        Ok(Pause { resume: Button::new(resume, glm::to_ivec2(window_size) / 2) })
+    }
+}
+
+impl GameState for Pause {
+    fn update<P, L>(&mut self,
+                    input: &InputManager<P>,
+                    _: &mut Context,
+                    _: &L)
+                    -> Result<Option<Transition>>
+        where P: EventPump,
+              L: ResourceLoader
+    {
+        let clicked = input.did_click_mouse(MouseButton::Left) &&
+                      self.resume.contains(input.mouse_coords());
+        if clicked || input.did_press_key(Keycode::P) {
+            Ok(Some(Transition::POP(1)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn draw<R: Renderer>(&self, _: f64, renderer: &mut R) -> Result<()> {
+        renderer.show(&self.resume).map_err(Into::into)
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
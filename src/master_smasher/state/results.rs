@@ -0,0 +1,122 @@
+use master_smasher::drawable::{Button, StarRating, StarRatingAssets};
+use master_smasher::drawable::layout;
+use master_smasher::level::{Outcome, Score};
+use super::{Context, GameState, Gameplay, State, Transition};
+use errors::*;
+
+use glm;
+use moho::errors as moho_errors;
+use moho::input_manager::{EventPump, InputManager};
+use moho::resource_manager::{Renderer, ResourceLoader, Scene, Texture};
+use sdl2::mouse::MouseButton;
+
+#[derive(Clone)]
+pub struct ResultsAssets {
+    stars: StarRatingAssets,
+    shot: Texture,
+    next: Texture,
+}
+
+impl ResultsAssets {
+    pub fn new<L: ResourceLoader>(resource_loader: &L) -> moho_errors::Result<Self> {
+        let stars = StarRatingAssets::new(resource_loader)?;
+        let shot = resource_loader.load_texture("resources/meteor.png")?;
+        let next = resource_loader.load_texture("resources/NextButton.png")?;
+        Ok(ResultsAssets {
+               stars: stars,
+               shot: shot,
+               next: next,
+           })
+    }
+}
+
+/// Overlay shown once a level is over: the stars collected, a meteor per shot used and a
+/// button to move on.
+pub struct Results {
+    index: usize,
+    outcome: Outcome,
+    stars: StarRating,
+    shots: Vec<glm::IVec4>,
+    shot: Texture,
+    next: Button,
+}
+
+impl Results {
+    pub fn new(index: usize,
+               outcome: Outcome,
+               score: Score,
+               assets: ResultsAssets,
+               window_size: glm::UVec2)
+               -> Self {
+        let center = glm::to_ivec2(window_size) / 2;
+        let stars = StarRating::new(score.stars,
+                                    score.total_stars,
+                                    1.,
+                                    center - glm::ivec2(0, 100),
+                                    assets.stars);
+        let shots = layout::row(score.shots, glm::ivec2(30, 30), center);
+        let next = Button::new(assets.next, center + glm::ivec2(0, 100));
+
+        Results {
+            index: index,
+            outcome: outcome,
+            stars: stars,
+            shots: shots,
+            shot: assets.shot,
+            next: next,
+        }
+    }
+
+    /// Cleared levels move on to the next one, back to the menu after the last; failed ones
+    /// are retried.
+    fn next<L: ResourceLoader>(&self,
+                               context: &mut Context,
+                               resource_loader: &L)
+                               -> Result<Transition> {
+        let next = match self.outcome {
+            Outcome::CLEARED => self.index + 1,
+            Outcome::FAILED => self.index,
+        };
+        if next < context.campaign.len() {
+            let gameplay = Gameplay::load(next, context, resource_loader)?;
+            Ok(Transition::REPLACE(2, State::PLAYING(gameplay)))
+        } else {
+            Ok(Transition::POP(2))
+        }
+    }
+}
+
+impl GameState for Results {
+    fn update<P, L>(&mut self,
+                    input: &InputManager<P>,
+                    context: &mut Context,
+                    resource_loader: &L)
+                    -> Result<Option<Transition>>
+        where P: EventPump,
+              L: ResourceLoader
+    {
+        if input.did_click_mouse(MouseButton::Left) && self.next.contains(input.mouse_coords()) {
+            self.next(context, resource_loader).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn draw<R: Renderer>(&self, _: f64, renderer: &mut R) -> Result<()> {
+        renderer.show(self).map_err(Into::into)
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+impl Scene for Results {
+    fn show<R: Renderer>(&self, renderer: &mut R) -> moho_errors::Result<()> {
+        renderer.show(&self.stars)?;
+        for rect in &self.shots {
+            renderer.render(&self.shot.id, *rect)?;
+        }
+        renderer.show(&self.next)
+    }
+}